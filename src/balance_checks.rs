@@ -0,0 +1,174 @@
+use crate::configuration::StatementBalance;
+use chrono::NaiveDate;
+use ledger_parser::{Amount, Balance, Commodity, CommodityPosition, Ledger, LedgerItem};
+use ledger_utils::simplified_ledger;
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+
+/// Expected balance of an account after the posting of the balance assertion
+/// or at the end of the day.
+pub struct BalanceCheck {
+    pub date: NaiveDate,
+    pub account: String,
+    /// Balance in the commodity, zero balance in all the commodities if not set.
+    pub expected: Option<Amount>,
+    /// `assertion` or `statement`.
+    pub source: String,
+    /// Position of the balance assertion in the simplified ledger of the file,
+    /// `posting` is the number of the postings of the transaction up to the assertion.
+    /// Not set for the statement balances (checked at the end of the day).
+    pub position: Option<PostingPosition>,
+}
+
+/// Indices of the ledger file, of the transaction in the file and of the posting.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PostingPosition {
+    pub file: usize,
+    pub transaction: usize,
+    pub posting: usize,
+}
+
+/// Balance assertions of the ledger files and known statement balances.
+#[derive(Default)]
+pub struct BalanceChecks {
+    pub checks: Vec<BalanceCheck>,
+    /// Postings marked by `take_assertions`.
+    marks: Vec<Mark>,
+}
+
+/// Posting of the balance assertion with its original comment.
+struct Mark {
+    check: usize,
+    comment: Option<String>,
+}
+
+/// Comment of the marked postings (with the mark index), kept by the simplification
+/// that may remove the postings before them or split them by commodities.
+const MARK_PREFIX: &str = "\u{0}balance-check:";
+
+impl BalanceChecks {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Removes the balance assertions (postings with both amount and balance) from the ledger,
+    /// so the not matching balances are reported instead of failing the simplification.
+    /// Balance assignments (postings without amount) are kept.
+    /// The positions of the assertions are found by `locate_assertions` after the simplification.
+    pub fn take_assertions(&mut self, ledger: &mut Ledger) {
+        let transactions = ledger.items.iter_mut().filter_map(|item| match item {
+            LedgerItem::Transaction(transaction) => Some(transaction),
+            _ => None,
+        });
+        for transaction in transactions {
+            for posting in &mut transaction.postings {
+                if posting.amount.is_none() {
+                    continue;
+                }
+                if let Some(balance) = posting.balance.take() {
+                    self.marks.push(Mark {
+                        check: self.checks.len(),
+                        comment: posting.comment.take(),
+                    });
+                    posting.comment = Some(format!("{}{}", MARK_PREFIX, self.marks.len() - 1));
+                    self.checks.push(BalanceCheck {
+                        date: transaction.date,
+                        account: posting.account.clone(),
+                        expected: match balance {
+                            Balance::Zero => None,
+                            Balance::Amount(amount) => Some(amount),
+                        },
+                        source: "assertion".to_string(),
+                        // see `locate_assertions`
+                        position: None,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Sets the positions of the balance assertions of the file (see `take_assertions`)
+    /// and restores the comments of the marked postings.
+    pub fn locate_assertions(&mut self, ledger: &mut simplified_ledger::Ledger, file: usize) {
+        for (transaction_index, transaction) in ledger.transactions.iter_mut().enumerate() {
+            for (posting_index, posting) in transaction.postings.iter_mut().enumerate() {
+                let mark = match posting
+                    .comment
+                    .as_deref()
+                    .and_then(|comment| comment.strip_prefix(MARK_PREFIX))
+                    .and_then(|mark| mark.parse::<usize>().ok())
+                {
+                    Some(mark) => &self.marks[mark],
+                    None => continue,
+                };
+                posting.comment = mark.comment.clone();
+                self.checks[mark.check].position = Some(PostingPosition {
+                    file,
+                    transaction: transaction_index,
+                    posting: posting_index + 1,
+                });
+            }
+        }
+    }
+
+    pub fn insert_statements(&mut self, statements: &[StatementBalance], main_commodity: &str) {
+        for statement in statements {
+            self.checks.push(BalanceCheck {
+                date: statement.date,
+                account: statement.account.clone(),
+                expected: Some(get_amount(
+                    statement.amount,
+                    statement.commodity.as_deref().unwrap_or(main_commodity),
+                )),
+                source: "statement".to_string(),
+                position: None,
+            });
+        }
+    }
+
+    /// Statement balances from the CSV file with `date,account,amount[,commodity]` lines.
+    /// Dates are `YYYY-MM-DD`, lines that do not start with a date (e.g. header) are skipped.
+    pub fn insert_from_csv_file(
+        &mut self,
+        file_name: &str,
+        main_commodity: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        for line in std::fs::read_to_string(file_name)?.lines() {
+            let mut columns = line.split([',', ';']).map(str::trim);
+            let date = match columns
+                .next()
+                .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+            {
+                Some(date) => date,
+                None => continue,
+            };
+            let account = columns.next().unwrap_or_default();
+            let amount = Decimal::from_str(columns.next().unwrap_or_default()).map_err(|err| {
+                format!("{}: wrong amount in line '{}': {}", file_name, line, err)
+            })?;
+            let commodity = columns
+                .next()
+                .filter(|commodity| !commodity.is_empty())
+                .unwrap_or(main_commodity);
+            self.checks.push(BalanceCheck {
+                date,
+                account: account.to_string(),
+                expected: Some(get_amount(amount, commodity)),
+                source: "statement".to_string(),
+                position: None,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn get_amount(quantity: Decimal, commodity: &str) -> Amount {
+    Amount {
+        quantity,
+        commodity: Commodity {
+            name: commodity.to_string(),
+            position: CommodityPosition::Right,
+        },
+    }
+}
@@ -1,11 +1,60 @@
-use serde::Deserialize;
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
 
 #[derive(Deserialize)]
 pub struct Configuration {
     pub ledger_files: Vec<String>,
     pub report_file: String,
 
+    /// Directory with user templates that override the built-in ones.
+    #[serde(default)]
+    pub templates_dir: Option<String>,
+    #[serde(default)]
+    pub theme: Theme,
+    /// Only the postings with the tag (`tag`, `key` or `key=value`) are reported.
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// CSV files with the price history (e.g. exported exchange rates or fund prices).
+    #[serde(default)]
+    pub price_files: Vec<PriceFile>,
+
     pub report_params: ReportParameters,
+
+    #[serde(default)]
+    pub budget: Budget,
+
+    #[serde(default)]
+    pub inflation: Inflation,
+
+    #[serde(default)]
+    pub investments: Vec<InvestmentGroup>,
+
+    #[serde(default)]
+    pub allocation: Allocation,
+
+    #[serde(default)]
+    pub forecast: Forecast,
+
+    #[serde(default)]
+    pub anomalies: Anomalies,
+
+    #[serde(default)]
+    pub payees: Payees,
+
+    #[serde(default)]
+    pub tags: Tags,
+
+    #[serde(default)]
+    pub register: Register,
+
+    #[serde(default)]
+    pub reconcile: Reconcile,
+
+    #[serde(default)]
+    pub data_quality: DataQuality,
 }
 
 #[derive(Deserialize)]
@@ -16,6 +65,8 @@ pub struct ReportParameters {
     pub assets: Vec<String>,
     pub income: Vec<String>,
     pub expenses: Vec<String>,
+    #[serde(default)]
+    pub liabilities: Vec<String>,
 
     pub assets_liquid: Vec<String>,
     pub assets_fixed: Vec<String>,
@@ -23,25 +74,449 @@ pub struct ReportParameters {
 
     pub job_income: Vec<String>,
     pub investment_income: Vec<String>,
+
+    /// Depth of the expense categories (below `expenses` accounts)
+    /// in the expense breakdown report.
+    #[serde(default = "default_expenses_categories_depth")]
+    pub expenses_categories_depth: usize,
+
+    #[serde(default)]
+    pub moving_averages: MovingAverages,
 }
 
-use std::ops::Deref;
+fn default_expenses_categories_depth() -> usize {
+    1
+}
+
+/// Moving averages drawn on the charts.
+#[derive(Deserialize)]
+pub struct MovingAverages {
+    #[serde(default = "default_moving_averages")]
+    pub expenses: Vec<MovingAverage>,
+    #[serde(default = "default_moving_averages")]
+    pub income: Vec<MovingAverage>,
+    /// Number of months of the trailing savings rate.
+    #[serde(default = "default_window")]
+    pub savings_rate: usize,
+}
+
+impl Default for MovingAverages {
+    fn default() -> Self {
+        MovingAverages {
+            expenses: default_moving_averages(),
+            income: default_moving_averages(),
+            savings_rate: default_window(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MovingAverage {
+    pub kind: MovingAverageKind,
+    pub window: usize,
+}
+
+impl MovingAverage {
+    /// Name of the chart serie, e.g. `SMA12`.
+    pub fn name(&self) -> String {
+        match self.kind {
+            MovingAverageKind::Sma => format!("SMA{}", self.window),
+            MovingAverageKind::Ema => format!("EMA{}", self.window),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum MovingAverageKind {
+    Sma,
+    Ema,
+}
+
+fn default_window() -> usize {
+    12
+}
+
+fn default_moving_averages() -> Vec<MovingAverage> {
+    vec![MovingAverage {
+        kind: MovingAverageKind::Sma,
+        window: default_window(),
+    }]
+}
+
+/// Budgeted amounts per expense or income account (including sub-accounts).
+#[derive(Deserialize, Default)]
+pub struct Budget {
+    #[serde(default)]
+    pub monthly: BTreeMap<String, Decimal>,
+    #[serde(default)]
+    pub yearly: BTreeMap<String, Decimal>,
+}
+
+/// Investment accounts whose performance is reported together.
+#[derive(Deserialize)]
+pub struct InvestmentGroup {
+    pub name: String,
+    pub accounts: Vec<String>,
+}
+
+/// Target allocation of the assets.
+#[derive(Deserialize, Default)]
+pub struct Allocation {
+    /// New money to invest while rebalancing.
+    #[serde(default)]
+    pub contribution: Decimal,
+    /// Liquid, fixed and high risk assets (without targets) by default.
+    #[serde(default)]
+    pub classes: Vec<AssetClass>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct AssetClass {
+    pub name: String,
+    pub accounts: Vec<String>,
+    /// Target share in percents.
+    #[serde(default)]
+    pub target: Option<Decimal>,
+}
+
+/// Price index (e.g. CPI) used to present the monthly values in real terms.
+#[derive(Deserialize, Default)]
+pub struct Inflation {
+    /// Commodity with the price index values (`P` directives in the ledger files
+    /// or the price files).
+    #[serde(default)]
+    pub commodity: Option<String>,
+    /// CSV file with `date,value` lines (used instead of the commodity).
+    #[serde(default)]
+    pub csv_file: Option<String>,
+    /// Show the monthly values in real terms (requires the commodity or the CSV file).
+    #[serde(default)]
+    pub real_values: bool,
+    /// Month (`YYYY-MM`) whose prices are used for the real values, the last month by default.
+    #[serde(default)]
+    pub base_month: Option<Month>,
+}
+
+/// Projection of the assets (see the forecast report).
+#[derive(Deserialize)]
+pub struct Forecast {
+    /// Number of the projected months.
+    #[serde(default = "default_forecast_months")]
+    pub months: u32,
+    /// Number of the last months used to calculate average income and expenses.
+    #[serde(default = "default_window")]
+    pub average_months: usize,
+    /// Minimal liquid assets.
+    #[serde(default)]
+    pub emergency_fund: Option<Decimal>,
+    /// Known future income (positive) and expenses (negative)
+    /// in addition to the average ones.
+    #[serde(default)]
+    pub scheduled: Vec<ScheduledAmount>,
+}
+
+impl Default for Forecast {
+    fn default() -> Self {
+        Forecast {
+            months: default_forecast_months(),
+            average_months: default_window(),
+            emergency_fund: None,
+            scheduled: Vec::new(),
+        }
+    }
+}
+
+fn default_forecast_months() -> u32 {
+    24
+}
+
+/// Detection of unusual expenses (see the anomalies report).
+#[derive(Deserialize)]
+pub struct Anomalies {
+    /// Months with expenses of a category above its trailing mean
+    /// by more standard deviations are flagged.
+    #[serde(default = "default_std_deviations")]
+    pub std_deviations: Decimal,
+    /// Minimal increase above the trailing mean (percent of the mean),
+    /// so small changes of constant expenses (e.g. rent) are not flagged.
+    #[serde(default = "default_min_increase_percent")]
+    pub min_increase_percent: Decimal,
+    /// Number of the previous months of the trailing mean (at least 3).
+    #[serde(default = "default_window")]
+    pub trailing_months: usize,
+    /// Transactions larger than the average previous transaction of the category
+    /// multiplied by the factor are flagged.
+    #[serde(default = "default_large_transaction")]
+    pub large_transaction: Decimal,
+    /// Settings of particular expense categories (e.g. `Expenses:Travel`).
+    #[serde(default)]
+    pub categories: BTreeMap<String, CategoryAnomalies>,
+}
+
+impl Default for Anomalies {
+    fn default() -> Self {
+        Anomalies {
+            std_deviations: default_std_deviations(),
+            min_increase_percent: default_min_increase_percent(),
+            trailing_months: default_window(),
+            large_transaction: default_large_transaction(),
+            categories: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_std_deviations() -> Decimal {
+    Decimal::new(2, 0)
+}
+
+fn default_min_increase_percent() -> Decimal {
+    Decimal::new(10, 0)
+}
+
+fn default_large_transaction() -> Decimal {
+    Decimal::new(5, 0)
+}
+
+#[derive(Deserialize)]
+pub struct CategoryAnomalies {
+    #[serde(default)]
+    pub std_deviations: Option<Decimal>,
+    #[serde(default)]
+    pub large_transaction: Option<Decimal>,
+    /// The category is never flagged.
+    #[serde(default)]
+    pub ignore: bool,
+}
+
+/// Spending by payee (transaction description).
+#[derive(Deserialize)]
+pub struct Payees {
+    /// Number of the last months of the payee report.
+    #[serde(default = "default_window")]
+    pub months: usize,
+    /// Number of the reported payees (the biggest ones).
+    #[serde(default = "default_top_payees")]
+    pub top: usize,
+    /// Payee names by their variants (e.g. `"LIDL SP Z OO" = "Lidl"`), case insensitive.
+    /// Variants ending with `*` match the description prefix (e.g. `"LIDL*"`),
+    /// variants starting and ending with `*` match its part (e.g. `"*NETFLIX*"`).
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+}
+
+impl Default for Payees {
+    fn default() -> Self {
+        Payees {
+            months: default_window(),
+            top: default_top_payees(),
+            aliases: BTreeMap::new(),
+        }
+    }
+}
+
+impl Payees {
+    /// Payee name of the transaction description.
+    /// Exact variants take precedence over the patterns.
+    pub fn normalize(&self, description: &str) -> String {
+        let description = description.trim();
+        let lowercase = description.to_lowercase();
+        self.aliases
+            .iter()
+            .find(|(variant, _)| variant.trim().to_lowercase() == lowercase)
+            .or_else(|| {
+                self.aliases.iter().find(|(variant, _)| {
+                    matches_pattern(&variant.trim().to_lowercase(), &lowercase)
+                })
+            })
+            .map(|(_, name)| name.clone())
+            .unwrap_or_else(|| description.to_string())
+    }
+}
+
+/// `prefix*` or `*part*` pattern (lowercase).
+fn matches_pattern(pattern: &str, lowercase: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(pattern) => match pattern.strip_prefix('*') {
+            Some(part) => !part.is_empty() && lowercase.contains(part),
+            None => !pattern.is_empty() && lowercase.starts_with(pattern),
+        },
+        None => false,
+    }
+}
+
+fn default_top_payees() -> usize {
+    20
+}
+
+/// Expenses per tag and per value of the metadata (`; key: value`) keys.
+#[derive(Deserialize, Default)]
+pub struct Tags {
+    /// Reported metadata keys (e.g. `trip`), all by default.
+    #[serde(default)]
+    pub keys: Vec<String>,
+}
+
+/// Postings of the register (the transactions report and the account pages).
+#[derive(Deserialize)]
+pub struct Register {
+    /// Number of the last months of the register, all the postings when 0.
+    #[serde(default = "default_window")]
+    pub months: usize,
+}
+
+impl Default for Register {
+    fn default() -> Self {
+        Register {
+            months: default_window(),
+        }
+    }
+}
+
+/// Known balances of the accounts (e.g. from bank statements)
+/// checked together with the balance assertions of the ledger files.
+#[derive(Deserialize, Default)]
+pub struct Reconcile {
+    #[serde(default)]
+    pub statements: Vec<StatementBalance>,
+    /// CSV file with `date,account,amount[,commodity]` lines.
+    #[serde(default)]
+    pub csv_file: Option<String>,
+}
+
+/// Balance of the account (without sub-accounts) at the end of the day.
+#[derive(Deserialize)]
+pub struct StatementBalance {
+    pub account: String,
+    /// `YYYY-MM-DD`
+    pub date: NaiveDate,
+    pub amount: Decimal,
+    /// The main commodity by default.
+    #[serde(default)]
+    pub commodity: Option<String>,
+}
 
-pub trait OptionDeref<T: Deref> {
-    fn as_deref(&self) -> Option<&T::Target>;
+/// Checks of the data quality report.
+#[derive(Deserialize)]
+pub struct DataQuality {
+    /// Accounts of the not categorized postings (e.g. `Expenses:Unknown`).
+    #[serde(default)]
+    pub catch_all_accounts: Vec<String>,
+    /// Prices older than the number of days are reported.
+    #[serde(default = "default_stale_price_days")]
+    pub stale_price_days: i64,
 }
 
-impl<T: Deref> OptionDeref<T> for Option<T> {
-    fn as_deref(&self) -> Option<&T::Target> {
-        self.as_ref().map(Deref::deref)
+impl Default for DataQuality {
+    fn default() -> Self {
+        DataQuality {
+            catch_all_accounts: Vec::new(),
+            stale_price_days: default_stale_price_days(),
+        }
     }
 }
 
+fn default_stale_price_days() -> i64 {
+    31
+}
+
+/// CSV file with the prices, `date,commodity,price,currency` columns by default.
+/// Lines without a date in the date column (e.g. header) are skipped.
+#[derive(Deserialize)]
+pub struct PriceFile {
+    pub file: String,
+    /// `chrono` format of the dates.
+    #[serde(default = "default_price_date_format")]
+    pub date_format: String,
+    /// Separator of the columns, detected from the first line by default
+    /// (`;` or tab if it contains them, `,` otherwise).
+    #[serde(default)]
+    pub delimiter: Option<char>,
+
+    /// Numbers of the columns (starting from 0).
+    #[serde(default)]
+    pub date_column: usize,
+    #[serde(default = "default_commodity_column")]
+    pub commodity_column: usize,
+    #[serde(default = "default_price_column")]
+    pub price_column: usize,
+    #[serde(default = "default_currency_column")]
+    pub currency_column: usize,
+
+    /// Commodity of all the prices (for files without the commodity column).
+    #[serde(default)]
+    pub commodity: Option<String>,
+    /// Currency of all the prices (for files without the currency column).
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+fn default_price_date_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+fn default_commodity_column() -> usize {
+    1
+}
+
+fn default_price_column() -> usize {
+    2
+}
+
+fn default_currency_column() -> usize {
+    3
+}
+
+#[derive(Deserialize)]
+pub struct ScheduledAmount {
+    pub name: String,
+    pub amount: Decimal,
+    /// The first month.
+    pub month: Month,
+    /// Repeats every number of months, once if not set.
+    #[serde(default)]
+    pub every_months: Option<u32>,
+    /// The last month of the repeated amount.
+    #[serde(default)]
+    pub until: Option<Month>,
+}
+
+/// Month given as `YYYY-MM`.
+#[derive(Deserialize, Clone, Copy, PartialEq, PartialOrd)]
+#[serde(try_from = "String")]
+pub struct Month {
+    pub year: i32,
+    pub month: u32,
+}
+
+impl TryFrom<String> for Month {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        let date = NaiveDate::parse_from_str(&format!("{}-01", text), "%Y-%m-%d")
+            .map_err(|_| format!("wrong month '{}', expected YYYY-MM", text))?;
+        Ok(Month {
+            year: date.year(),
+            month: date.month(),
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+}
+
+use std::ops::Deref;
+
 pub trait VecDeref<T: Deref> {
     fn as_deref(&self) -> Vec<&T::Target>;
 }
 
-impl<T: Deref> VecDeref<T> for Vec<T> {
+impl<T: Deref> VecDeref<T> for [T] {
     fn as_deref(&self) -> Vec<&T::Target> {
         self.iter().map(Deref::deref).collect::<Vec<&T::Target>>()
     }
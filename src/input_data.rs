@@ -1,3 +1,11 @@
+use crate::balance_checks::BalanceChecks;
+use crate::configuration::Configuration;
+use crate::metadata::filter_by_tag;
+use crate::posting_costs::PostingCosts;
+use crate::price_files::read_price_file;
+use crate::price_index::PriceIndex;
+use crate::price_sources::PriceSources;
+use crate::report_data::{get_reconcile_report, ReconcileReport};
 use ledger_parser::*;
 use ledger_utils::join_ledgers::join_ledgers;
 use ledger_utils::prices::Prices;
@@ -8,32 +16,84 @@ use std::error::Error;
 pub struct InputData {
     pub ledger: simplified_ledger::Ledger,
     pub prices: Prices,
+    pub price_sources: PriceSources,
+    pub posting_costs: PostingCosts,
+    pub price_index: Option<PriceIndex>,
+    /// Computed from the whole ledger (before the tag filter and the currency conversions).
+    pub reconcile_report: ReconcileReport,
 }
 
 impl InputData {
-    pub fn load(ledger_files: &Vec<String>) -> Result<InputData, Box<dyn Error>> {
-        let ledgers: Result<Vec<Ledger>, Box<dyn Error>> = ledger_files
+    pub fn load(configuration: &Configuration) -> Result<InputData, Box<dyn Error>> {
+        let ledgers: Result<Vec<Ledger>, Box<dyn Error>> = configuration
+            .ledger_files
             .iter()
             .map(|file_name| Ok(parse(&std::fs::read_to_string(file_name)?)?))
             .collect();
-        let ledgers = ledgers?;
+        let mut ledgers = ledgers?;
 
         let mut prices = Prices::new();
-        for ledger in &ledgers {
+        let mut price_sources = PriceSources::new();
+        let mut posting_costs = PostingCosts::new();
+        let mut balance_checks = BalanceChecks::new();
+
+        // prices of the ledger files override the ones of the price files (from the same day)
+        for price_file in &configuration.price_files {
+            let price_ledger = read_price_file(price_file)?;
+            prices.insert_from(&price_ledger);
+            price_sources.insert_from_price_file(&price_ledger, &price_file.file);
+        }
+        for (file_name, ledger) in configuration.ledger_files.iter().zip(&mut ledgers) {
             prices.insert_from(ledger);
+            price_sources.insert_from(ledger, file_name);
+            posting_costs.insert_from(ledger);
+            balance_checks.take_assertions(ledger);
+        }
+
+        let main_commodity = &configuration.report_params.main_commodity;
+        balance_checks.insert_statements(&configuration.reconcile.statements, main_commodity);
+        if let Some(csv_file) = &configuration.reconcile.csv_file {
+            balance_checks.insert_from_csv_file(csv_file, main_commodity)?;
         }
 
         let simplified_ledgers: Result<Vec<simplified_ledger::Ledger>, SimplificationError> =
             ledgers
                 .into_iter()
-                .map(|ledger| simplified_ledger::Ledger::try_from(ledger))
+                .map(simplified_ledger::Ledger::try_from)
                 .collect();
+        let mut simplified_ledgers = simplified_ledgers?;
+        for (file, ledger) in simplified_ledgers.iter_mut().enumerate() {
+            balance_checks.locate_assertions(ledger, file);
+        }
+        let reconcile_report = get_reconcile_report(&simplified_ledgers, &balance_checks);
+
+        let mut simplified_ledger = join_ledgers(simplified_ledgers);
+        if let Some(tag) = &configuration.tag {
+            filter_by_tag(&mut simplified_ledger, tag);
+        }
 
-        let simplified_ledger = join_ledgers(simplified_ledgers?);
+        let inflation = &configuration.inflation;
+        let price_index = if let Some(csv_file) = &inflation.csv_file {
+            Some(PriceIndex::from_csv_file(csv_file)?)
+        } else {
+            inflation
+                .commodity
+                .as_ref()
+                .map(|commodity| PriceIndex::from_prices(&prices, commodity, main_commodity))
+        };
+        if inflation.real_values && price_index.as_ref().is_none_or(PriceIndex::is_empty) {
+            return Err("real values require a price index \
+                (`commodity` with prices or `csv_file` in the `[inflation]` section)"
+                .into());
+        }
 
         Ok(InputData {
             ledger: simplified_ledger,
             prices,
+            price_sources,
+            posting_costs,
+            price_index,
+            reconcile_report,
         })
     }
 }
@@ -1,8 +1,16 @@
+mod balance_checks;
 mod configuration;
 mod date_utils;
 mod input_data;
+mod metadata;
+mod posting_costs;
+mod price_files;
+mod price_index;
+mod price_sources;
 mod report;
 mod report_data;
+mod template_helpers;
+mod template_loader;
 
 use clap::{App, Arg, ArgMatches};
 use std::error::Error;
@@ -10,6 +18,7 @@ use std::error::Error;
 use crate::configuration::Configuration;
 use ledger_parser::{Serializer, SerializerSettings};
 use ledger_utils::handle_foreign_currencies::handle_foreign_currencies;
+use ledger_utils::monthly_report::MonthlyReport;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let matches = App::new("rust_reports")
@@ -23,18 +32,60 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .help("Sets a custom config file (toml)")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("site")
+                .long("site")
+                .value_name("DIR")
+                .help("Generates a static site (multiple pages) in the directory")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("tag")
+                .long("tag")
+                .value_name("TAG")
+                .help("Reports only the postings with the tag (tag, key or key=value)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("reconcile")
+                .long("reconcile")
+                .help("Checks the balance assertions and statement balances, prints the mismatches"),
+        )
+        .arg(
+            Arg::with_name("explain-valuation")
+                .long("explain-valuation")
+                .help("Prints the prices applied at every report date and the missing prices"),
+        )
+        .arg(
+            Arg::with_name("real-values")
+                .long("real-values")
+                .help("Shows the monthly values in real terms (see [inflation] config section)"),
+        )
         .get_matches();
 
-    let configuration = read_configuration(&matches)?;
+    let mut configuration = read_configuration(&matches)?;
+    if matches.is_present("real-values") {
+        configuration.inflation.real_values = true;
+    }
+    if let Some(tag) = matches.value_of("tag") {
+        configuration.tag = Some(tag.to_string());
+    }
 
-    let mut input_data = input_data::InputData::load(&configuration.ledger_files)?;
+    let mut input_data = input_data::InputData::load(&configuration)?;
 
+    // liabilities are handled as (negative) assets,
+    // e.g. paying off a credit card in foreign currency is an exchange between assets
     let is_asset_account = |account_name: &str| {
         for asset_prefix in &configuration.report_params.assets {
             if account_name.starts_with(asset_prefix) {
                 return true;
             }
         }
+        for liability_prefix in &configuration.report_params.liabilities {
+            if account_name.starts_with(liability_prefix) {
+                return true;
+            }
+        }
         false
     };
 
@@ -68,6 +119,14 @@ fn main() -> Result<(), Box<dyn Error>> {
         panic!("{:?}", err);
     }
 
+    if matches.is_present("reconcile") {
+        return reconcile(&input_data);
+    }
+    if matches.is_present("explain-valuation") {
+        explain_valuation(&input_data, &configuration);
+        return Ok(());
+    }
+
     println!(
         "{}",
         input_data
@@ -75,12 +134,70 @@ fn main() -> Result<(), Box<dyn Error>> {
             .to_string_pretty(&SerializerSettings::default().with_indent("\t"))
     );
 
-    report::generate_report(
-        &configuration.report_file,
-        &input_data.ledger,
+    if let Some(site_dir) = matches.value_of("site") {
+        report::generate_site(site_dir, &input_data, &configuration)
+    } else {
+        report::generate_report(&configuration.report_file, &input_data, &configuration)
+    }
+}
+
+/// Prints the balance mismatches, fails if there are any.
+fn reconcile(input_data: &input_data::InputData) -> Result<(), Box<dyn Error>> {
+    let report = &input_data.reconcile_report;
+    for row in report.rows.iter().rev().filter(|row| !row.is_matching) {
+        println!(
+            "{} {} ({}): expected {}, actual {}, difference {}",
+            row.date, row.account, row.source, row.expected, row.actual, row.difference
+        );
+        if let Some(transaction) = &row.first_unverified {
+            println!(
+                "    first unverified {} {}: {}",
+                transaction.date, transaction.description, transaction.amount
+            );
+        }
+        for transaction in &row.suspects {
+            println!(
+                "    suspect {} {}: {}",
+                transaction.date, transaction.description, transaction.amount
+            );
+        }
+    }
+    println!(
+        "{} balances checked, {} mismatches",
+        report.rows.len(),
+        report.mismatches
+    );
+
+    if report.mismatches > 0 {
+        Err(format!("{} balance mismatches", report.mismatches).into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Prints the valuation trace and warnings.
+fn explain_valuation(input_data: &input_data::InputData, configuration: &Configuration) {
+    let trace = report_data::get_valuation_trace(
+        &MonthlyReport::from(&input_data.ledger),
         &input_data.prices,
+        &input_data.price_sources,
         &configuration.report_params,
-    )
+    );
+    for valuation_date in &trace.dates {
+        println!("{}", valuation_date.date);
+        for price in &valuation_date.prices {
+            println!(
+                "    {} = {} {} (price from {}, {})",
+                price.commodity, price.price, trace.main_commodity, price.price_date, price.source
+            );
+        }
+        for commodity in &valuation_date.missing {
+            println!("    {}: no price, not counted", commodity);
+        }
+    }
+    for warning in &trace.warnings {
+        println!("warning: {}", warning);
+    }
 }
 
 fn read_configuration(matches: &ArgMatches) -> Result<Configuration, Box<dyn Error>> {
@@ -0,0 +1,155 @@
+use ledger_utils::simplified_ledger;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Tags (`; :tag1:tag2:`) and values (`; key: value`) from the comments.
+#[derive(Default, Clone)]
+pub struct Metadata {
+    pub tags: BTreeSet<String>,
+    pub values: BTreeMap<String, String>,
+}
+
+impl Metadata {
+    pub fn from_comment(comment: Option<&str>) -> Self {
+        let mut metadata = Metadata::default();
+        for line in comment.unwrap_or_default().lines() {
+            let line = line.trim();
+            let first_word = line.split_whitespace().next().unwrap_or_default();
+            if first_word.len() > 1 && first_word.ends_with(':') && !first_word.starts_with(':') {
+                // `key: value` (also typed `key:: value`)
+                let (key, value) = line.split_at(line.find(':').unwrap());
+                let value = value.trim_start_matches(':').trim();
+                if value.is_empty() {
+                    metadata.tags.insert(key.to_string());
+                } else {
+                    metadata.values.insert(key.to_string(), value.to_string());
+                }
+            } else {
+                for word in line.split_whitespace() {
+                    if word.len() > 2 && word.starts_with(':') && word.ends_with(':') {
+                        metadata.tags.extend(
+                            word.split(':')
+                                .filter(|tag| !tag.is_empty())
+                                .map(|tag| tag.to_string()),
+                        );
+                    }
+                }
+            }
+        }
+        metadata
+    }
+
+    /// Metadata of the posting including the metadata of its transaction.
+    pub fn of_posting(
+        transaction: &simplified_ledger::Transaction,
+        posting: &simplified_ledger::Posting,
+    ) -> Self {
+        let mut metadata = Metadata::from_comment(transaction.comment.as_deref());
+        let posting_metadata = Metadata::from_comment(posting.comment.as_deref());
+        metadata.tags.extend(posting_metadata.tags);
+        metadata.values.extend(posting_metadata.values);
+        metadata
+    }
+
+    /// Checks the tag filter: `tag` (a tag or a key with any value) or `key=value`.
+    pub fn matches(&self, filter: &str) -> bool {
+        match filter.split_once('=') {
+            Some((key, value)) => self
+                .values
+                .get(key.trim())
+                .is_some_and(|v| v.eq_ignore_ascii_case(value.trim())),
+            None => {
+                let filter = filter.trim();
+                self.tags.contains(filter) || self.values.contains_key(filter)
+            }
+        }
+    }
+}
+
+/// Removes the postings not matching the tag filter (see `Metadata::matches`)
+/// and the transactions without postings.
+pub fn filter_by_tag(ledger: &mut simplified_ledger::Ledger, filter: &str) {
+    for transaction in &mut ledger.transactions {
+        let transaction_metadata = Metadata::from_comment(transaction.comment.as_deref());
+        if transaction_metadata.matches(filter) {
+            continue;
+        }
+        transaction
+            .postings
+            .retain(|posting| Metadata::from_comment(posting.comment.as_deref()).matches(filter));
+    }
+    ledger
+        .transactions
+        .retain(|transaction| !transaction.postings.is_empty());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn parses_tags_and_values() {
+        let metadata = Metadata::from_comment(Some(":trip:food:\ncity: Paris\nproject:: house"));
+        assert_eq!(
+            metadata.tags.iter().collect::<Vec<_>>(),
+            vec!["food", "trip"]
+        );
+        assert_eq!(metadata.values["city"], "Paris");
+        assert_eq!(metadata.values["project"], "house");
+    }
+
+    #[test]
+    fn key_without_value_is_tag() {
+        let metadata = Metadata::from_comment(Some("reviewed:"));
+        assert!(metadata.tags.contains("reviewed"));
+        assert!(metadata.values.is_empty());
+    }
+
+    #[test]
+    fn ignores_plain_comments() {
+        let metadata = Metadata::from_comment(Some("paid at 12:30 : cash"));
+        assert!(metadata.tags.is_empty());
+        assert!(metadata.values.is_empty());
+        assert!(Metadata::from_comment(None).tags.is_empty());
+    }
+
+    #[test]
+    fn matches_tag_key_and_value() {
+        let metadata = Metadata::from_comment(Some(":trip:\ncity: Paris"));
+        assert!(metadata.matches("trip"));
+        assert!(metadata.matches("city"));
+        assert!(metadata.matches("city=paris"));
+        assert!(!metadata.matches("city=Rome"));
+        assert!(!metadata.matches("project"));
+    }
+
+    #[test]
+    fn filter_keeps_tagged_postings_and_transactions() {
+        let ledger = ledger_parser::parse(
+            r#"
+2024-01-01 Hotel
+    ; :trip:
+    Expenses:Hotel  100 PLN
+    Assets:Bank  -100 PLN
+
+2024-01-02 Shop
+    Expenses:Food  10 PLN
+    ; :trip:
+    Expenses:Home  20 PLN
+    Assets:Bank  -30 PLN
+"#,
+        )
+        .unwrap();
+        let mut ledger = simplified_ledger::Ledger::try_from(ledger).unwrap();
+        filter_by_tag(&mut ledger, "trip");
+
+        assert_eq!(ledger.transactions.len(), 2);
+        assert_eq!(ledger.transactions[0].postings.len(), 2);
+        let accounts: Vec<&str> = ledger.transactions[1]
+            .postings
+            .iter()
+            .map(|posting| posting.account.as_str())
+            .collect();
+        assert_eq!(accounts, vec!["Expenses:Food"]);
+    }
+}
@@ -0,0 +1,68 @@
+use chrono::NaiveDate;
+use ledger_parser::{Amount, Ledger, LedgerItem, Price};
+use ledger_utils::simplified_ledger;
+use rust_decimal::prelude::Signed;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// Costs of the postings given with `@` (unit) or `@@` (total) prices.
+/// The simplified ledger keeps only the posting amounts.
+#[derive(Default)]
+pub struct PostingCosts {
+    /// Total costs (with the sign of the posting quantity)
+    /// by date, account, commodity and quantity of the posting.
+    costs: HashMap<(NaiveDate, String, String, Decimal), Amount>,
+}
+
+impl PostingCosts {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn insert_from(&mut self, ledger: &Ledger) {
+        for item in &ledger.items {
+            if let LedgerItem::Transaction(transaction) = item {
+                for posting in &transaction.postings {
+                    let posting_amount = match &posting.amount {
+                        Some(posting_amount) => posting_amount,
+                        None => continue,
+                    };
+                    let amount = &posting_amount.amount;
+                    let cost = match &posting_amount.price {
+                        Some(Price::Unit(price)) => Amount {
+                            quantity: price.quantity * amount.quantity,
+                            commodity: price.commodity.clone(),
+                        },
+                        Some(Price::Total(total)) => Amount {
+                            quantity: total.quantity.abs() * amount.quantity.signum(),
+                            commodity: total.commodity.clone(),
+                        },
+                        None => continue,
+                    };
+                    self.costs.insert(
+                        (
+                            transaction.date,
+                            posting.account.clone(),
+                            amount.commodity.name.clone(),
+                            amount.quantity,
+                        ),
+                        cost,
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn get_cost(
+        &self,
+        date: NaiveDate,
+        posting: &simplified_ledger::Posting,
+    ) -> Option<&Amount> {
+        self.costs.get(&(
+            date,
+            posting.account.clone(),
+            posting.amount.commodity.name.clone(),
+            posting.amount.quantity,
+        ))
+    }
+}
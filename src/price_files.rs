@@ -0,0 +1,125 @@
+use crate::configuration::PriceFile;
+use chrono::NaiveDate;
+use ledger_parser::{Amount, Commodity, CommodityPosition, CommodityPrice, Ledger, LedgerItem};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+
+/// Reads the prices of the CSV file as `P` directives,
+/// so they are added to the prices the same way as the ones of the ledger files.
+/// The first line is skipped if it does not start with a date (header).
+pub fn read_price_file(price_file: &PriceFile) -> Result<Ledger, Box<dyn Error>> {
+    let file_name = &price_file.file;
+    let content = std::fs::read_to_string(file_name)?;
+    let delimiter = price_file
+        .delimiter
+        .unwrap_or_else(|| detect_delimiter(content.lines().next().unwrap_or_default()));
+
+    let mut items = Vec::new();
+    for (line_index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let columns = split_line(line, delimiter);
+        let column = |pos: usize| {
+            columns
+                .get(pos)
+                .map(|column| column.trim())
+                .unwrap_or_default()
+        };
+
+        let date = match NaiveDate::parse_from_str(
+            column(price_file.date_column),
+            &price_file.date_format,
+        ) {
+            Ok(date) => date,
+            Err(_) if line_index == 0 => continue,
+            Err(err) => {
+                return Err(format!("{}: wrong date in line '{}': {}", file_name, line, err).into())
+            }
+        };
+
+        // decimal comma is allowed (in quotes when the columns are separated with commas),
+        // digit groups may be separated with spaces
+        let price: String = column(price_file.price_column)
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| if c == ',' { '.' } else { c })
+            .collect();
+        let price = Decimal::from_str(&price)
+            .ok()
+            .filter(|price| !price.is_zero())
+            .ok_or_else(|| format!("{}: wrong price in line '{}'", file_name, line))?;
+
+        let commodity = match &price_file.commodity {
+            Some(commodity) => commodity.as_str(),
+            None => column(price_file.commodity_column),
+        };
+        let currency = match &price_file.currency {
+            Some(currency) => currency.as_str(),
+            None => column(price_file.currency_column),
+        };
+        if commodity.is_empty() || currency.is_empty() {
+            return Err(format!(
+                "{}: missing commodity or currency in line '{}'",
+                file_name, line
+            )
+            .into());
+        }
+
+        items.push(LedgerItem::CommodityPrice(CommodityPrice {
+            datetime: date.and_hms(0, 0, 0),
+            commodity_name: commodity.to_string(),
+            amount: Amount {
+                quantity: price,
+                commodity: Commodity {
+                    name: currency.to_string(),
+                    position: CommodityPosition::Right,
+                },
+            },
+        }));
+    }
+    Ok(Ledger { items })
+}
+
+/// `;` or tab if the line (e.g. header) contains it, `,` otherwise.
+fn detect_delimiter(line: &str) -> char {
+    if line.contains(';') {
+        ';'
+    } else if line.contains('\t') {
+        '\t'
+    } else {
+        ','
+    }
+}
+
+/// Columns of the CSV line, the delimiter is allowed in the quoted columns
+/// (with `""` for the quote character).
+fn split_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut columns = Vec::new();
+    let mut column = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    column.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                column.push(c);
+            }
+        } else if c == '"' {
+            in_quotes = true;
+        } else if c == delimiter {
+            columns.push(std::mem::take(&mut column));
+        } else {
+            column.push(c);
+        }
+    }
+    columns.push(column);
+    columns
+}
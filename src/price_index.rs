@@ -0,0 +1,82 @@
+use chrono::NaiveDate;
+use ledger_utils::prices::Prices;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::str::FromStr;
+
+/// Price index (e.g. CPI) values in time, used to present values in real terms.
+pub struct PriceIndex {
+    values: BTreeMap<NaiveDate, Decimal>,
+}
+
+impl PriceIndex {
+    /// Price index from the prices of the commodity (`P` directives of the ledger files
+    /// and the price files), preferably in the main commodity.
+    pub fn from_prices(prices: &Prices, commodity: &str, main_commodity: &str) -> PriceIndex {
+        let rates = prices
+            .rates
+            .iter()
+            .filter(|(pair, _)| pair.src_commodity_name == commodity)
+            .min_by_key(|(pair, _)| {
+                (
+                    pair.dst_commodity_name != main_commodity,
+                    &pair.dst_commodity_name,
+                )
+            })
+            .map(|(_, rates)| rates.table.clone());
+        PriceIndex {
+            values: rates.unwrap_or_default(),
+        }
+    }
+
+    /// Price index from the CSV file with `date,value` lines.
+    /// Dates are `YYYY-MM-DD` or `YYYY-MM` (the first day of the month),
+    /// lines that do not start with a date (e.g. header) are skipped.
+    pub fn from_csv_file(file_name: &str) -> Result<PriceIndex, Box<dyn Error>> {
+        let mut values = BTreeMap::new();
+        for line in std::fs::read_to_string(file_name)?.lines() {
+            let mut columns = line.split([',', ';']).map(str::trim);
+            let date = match columns.next().and_then(parse_date) {
+                Some(date) => date,
+                None => continue,
+            };
+            let value = columns.next().unwrap_or_default();
+            let value = Decimal::from_str(value)
+                .map_err(|err| format!("{}: wrong value in line '{}': {}", file_name, line, err))?;
+            values.insert(date, value);
+        }
+        Ok(PriceIndex { values })
+    }
+
+    /// The latest value not after the date (none for dates before the first value).
+    pub fn get_value(&self, date: NaiveDate) -> Option<Decimal> {
+        self.values
+            .range(..=date)
+            .next_back()
+            .map(|(_, value)| *value)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn get_start_date(&self) -> Option<NaiveDate> {
+        self.values.keys().next().copied()
+    }
+
+    /// Multiplier converting values of the date into prices of the base date.
+    /// Values are not adjusted (factor 1) when there is no index value for any of the dates.
+    pub fn get_real_value_factor(&self, date: NaiveDate, base_date: NaiveDate) -> Decimal {
+        match (self.get_value(date), self.get_value(base_date)) {
+            (Some(value), Some(base_value)) if !value.is_zero() => base_value / value,
+            _ => Decimal::new(1, 0),
+        }
+    }
+}
+
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(&format!("{}-01", text), "%Y-%m-%d"))
+        .ok()
+}
@@ -0,0 +1,96 @@
+use chrono::NaiveDate;
+use ledger_parser::{Ledger, LedgerItem};
+use std::collections::HashMap;
+
+/// Origins of the prices (e.g. P directive or transaction and its file),
+/// shown in the valuation trace.
+#[derive(Default)]
+pub struct PriceSources {
+    /// Source by source commodity, destination commodity and date of the price.
+    sources: HashMap<(String, String, NaiveDate), String>,
+}
+
+impl PriceSources {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Records the sources in the order `Prices::insert_from` adds the prices,
+    /// so the later price of the same day overrides also the source.
+    pub fn insert_from(&mut self, ledger: &Ledger, file_name: &str) {
+        self.insert_commodity_prices(ledger, &format!("P directive in {}", file_name));
+
+        for item in &ledger.items {
+            if let LedgerItem::Transaction(transaction) = item {
+                if transaction.postings.len() != 2 {
+                    continue;
+                }
+                let amounts: Vec<_> = transaction
+                    .postings
+                    .iter()
+                    .filter_map(|posting| posting.amount.as_ref())
+                    .map(|posting_amount| &posting_amount.amount)
+                    .collect();
+                if let [amount1, amount2] = amounts[..] {
+                    if amount1.commodity.name != amount2.commodity.name
+                        && !amount1.quantity.is_zero()
+                        && !amount2.quantity.is_zero()
+                    {
+                        self.insert(
+                            &amount1.commodity.name,
+                            &amount2.commodity.name,
+                            transaction.date,
+                            format!(
+                                "transaction \"{}\" in {}",
+                                transaction.description, file_name
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Prices of the CSV file (see `read_price_file`).
+    pub fn insert_from_price_file(&mut self, ledger: &Ledger, file_name: &str) {
+        self.insert_commodity_prices(ledger, &format!("price file {}", file_name));
+    }
+
+    fn insert_commodity_prices(&mut self, ledger: &Ledger, source: &str) {
+        for item in &ledger.items {
+            if let LedgerItem::CommodityPrice(price) = item {
+                self.insert(
+                    &price.commodity_name,
+                    &price.amount.commodity.name,
+                    price.datetime.date(),
+                    source.to_string(),
+                );
+            }
+        }
+    }
+
+    /// Prices are used in both directions.
+    pub fn insert(&mut self, commodity: &str, currency: &str, date: NaiveDate, source: String) {
+        self.sources.insert(
+            (currency.to_string(), commodity.to_string(), date),
+            source.clone(),
+        );
+        self.sources
+            .insert((commodity.to_string(), currency.to_string(), date), source);
+    }
+
+    pub fn get_source(
+        &self,
+        src_commodity_name: &str,
+        dst_commodity_name: &str,
+        date: NaiveDate,
+    ) -> Option<&str> {
+        self.sources
+            .get(&(
+                src_commodity_name.to_string(),
+                dst_commodity_name.to_string(),
+                date,
+            ))
+            .map(String::as_str)
+    }
+}
@@ -1,28 +1,230 @@
-use crate::configuration::ReportParameters;
+use crate::configuration::Configuration;
+use crate::input_data::InputData;
 use std::fs::File;
+use std::path::Path;
 
-use crate::report_data::make_report_data;
-use handlebars::Handlebars;
-use ledger_utils::prices::Prices;
-use ledger_utils::simplified_ledger;
+use crate::report_data::{make_account_report_data, make_report_data};
+use crate::template_helpers::{account_file_name, register_helpers};
+use crate::template_loader::TemplateLoader;
+use handlebars::{to_json, Handlebars};
+use serde::Serialize;
+use serde_json::value::{Map, Value as Json};
 use std::error::Error;
 
+/// Reports (id, title) shown as tabs of the single file report
+/// and as separate pages of the static site.
+/// Every report is rendered with the template of the same id.
+const REPORTS: &[(&str, &str)] = &[
+    ("summary", "Summary"),
+    ("assets", "Assets"),
+    ("net_worth", "Net Worth"),
+    ("expenses", "Expenses"),
+    ("expense_categories", "Expense Categories"),
+    ("anomalies", "Anomalies"),
+    ("budget", "Budget"),
+    ("payees", "Payees"),
+    ("recurring", "Subscriptions"),
+    ("tags", "Tags"),
+    ("income", "Income"),
+    ("savings", "Savings"),
+    ("investments", "Investments"),
+    ("allocation", "Allocation"),
+    ("exposure", "Currency Exposure"),
+    ("attribution", "Change Attribution"),
+    ("forecast", "Forecast"),
+    ("year_over_year", "Year over Year"),
+    ("register", "Transactions"),
+    ("reconcile", "Reconcile"),
+    ("data_quality", "Data Quality"),
+];
+
+/// Reports of transactions and current balances that are not converted into real values
+/// (see [inflation] config section).
+const NOMINAL_REPORTS: &[&str] = &[
+    "summary",
+    "budget",
+    "payees",
+    "recurring",
+    "tags",
+    "investments",
+    "allocation",
+    "exposure",
+    "attribution",
+    "register",
+];
+
+#[derive(Serialize)]
+struct ReportPage {
+    id: String,
+    title: String,
+    file: String,
+    content: String,
+    nominal: bool,
+}
+
+/// Generates a single html file with all the reports.
 pub fn generate_report(
     output_file: &str,
-    ledger: &simplified_ledger::Ledger,
-    prices: &Prices,
-    report_params: &ReportParameters,
+    input_data: &InputData,
+    configuration: &Configuration,
 ) -> Result<(), Box<dyn Error>> {
-    let data = make_report_data(&ledger, &prices, &report_params);
+    let loader = TemplateLoader::new(configuration.templates_dir.as_deref());
+    let reg = make_registry(&loader)?;
+
+    let mut data = make_report_data(input_data, configuration);
+    data.insert("theme".to_string(), to_json(configuration.theme));
+    data.insert("site".to_string(), to_json(false));
+    data.insert("root_path".to_string(), to_json(""));
+    data.insert("html_style".to_string(), to_json(get_style(&loader)?));
+    data.insert("html_script".to_string(), to_json(get_script()));
+
+    let pages = render_report_pages(&reg, &data)?;
+    data.insert("pages".to_string(), to_json(pages));
 
-    let mut reg = Handlebars::new();
-    reg.register_template_string("main", include_str!("templates/main.hbs"))?;
-    reg.register_template_string("area_chart", include_str!("templates/area_chart.hbs"))?;
-    reg.register_template_string("line_chart", include_str!("templates/line_chart.hbs"))?;
-    reg.register_template_string("table", include_str!("templates/table.hbs"))?;
-    reg.register_template_string("tree", include_str!("templates/tree.hbs"))?;
-    reg.register_template_string("tree_node", include_str!("templates/tree_node.hbs"))?;
     reg.render_to_write("main", &data, File::create(output_file)?)?;
 
     Ok(())
 }
+
+/// Generates a static site with an index page, one page per report,
+/// drill-down pages per account and shared static assets.
+/// All the links are relative, so the site can be opened from disk.
+pub fn generate_site(
+    output_dir: &str,
+    input_data: &InputData,
+    configuration: &Configuration,
+) -> Result<(), Box<dyn Error>> {
+    let loader = TemplateLoader::new(configuration.templates_dir.as_deref());
+    let reg = make_registry(&loader)?;
+
+    let output_dir = Path::new(output_dir);
+    std::fs::create_dir_all(output_dir.join("static"))?;
+    std::fs::create_dir_all(output_dir.join("accounts"))?;
+    std::fs::write(output_dir.join("static/style.css"), get_style(&loader)?)?;
+    std::fs::write(output_dir.join("static/script.js"), get_script())?;
+
+    let mut data = make_report_data(input_data, configuration);
+    data.insert("theme".to_string(), to_json(configuration.theme));
+    data.insert("site".to_string(), to_json(true));
+    data.insert("root_path".to_string(), to_json(""));
+
+    let mut pages = render_report_pages(&reg, &data)?;
+    let contents: Vec<String> = pages
+        .iter_mut()
+        .map(|page| std::mem::take(&mut page.content))
+        .collect();
+    data.insert("pages".to_string(), to_json(&pages));
+
+    let index_content = reg.render("index", &data)?;
+    write_page(
+        &reg,
+        &data,
+        "Index",
+        "index.html",
+        index_content,
+        output_dir,
+    )?;
+
+    for (page, content) in pages.iter().zip(contents) {
+        write_page(&reg, &data, &page.title, &page.file, content, output_dir)?;
+    }
+
+    for mut account_data in make_account_report_data(
+        &input_data.ledger,
+        &input_data.prices,
+        &configuration.register,
+        &configuration.report_params,
+    ) {
+        let account = account_data["account"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        account_data.insert("theme".to_string(), to_json(configuration.theme));
+        account_data.insert("site".to_string(), to_json(true));
+        account_data.insert("root_path".to_string(), to_json("../"));
+        account_data.insert("pages".to_string(), to_json(&pages));
+
+        let content = reg.render("account", &account_data)?;
+        write_page(
+            &reg,
+            &account_data,
+            &account,
+            &account_file_name(&account),
+            content,
+            output_dir,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn make_registry(loader: &TemplateLoader) -> Result<Handlebars<'static>, Box<dyn Error>> {
+    let mut reg = Handlebars::new();
+    register_helpers(&mut reg);
+    reg.register_template_string("main", loader.load("main.hbs")?)?;
+    reg.register_template_string("page", loader.load("page.hbs")?)?;
+    reg.register_template_string("index", loader.load("index.hbs")?)?;
+    reg.register_template_string("account", loader.load("account.hbs")?)?;
+    reg.register_template_string("area_chart", loader.load("area_chart.hbs")?)?;
+    reg.register_template_string("line_chart", loader.load("line_chart.hbs")?)?;
+    reg.register_template_string("bar_chart", loader.load("bar_chart.hbs")?)?;
+    reg.register_template_string("waterfall_chart", loader.load("waterfall_chart.hbs")?)?;
+    reg.register_template_string("table", loader.load("table.hbs")?)?;
+    reg.register_template_string("register_table", loader.load("register_table.hbs")?)?;
+    reg.register_template_string("valuation_footer", loader.load("valuation_footer.hbs")?)?;
+    reg.register_template_string("tree", loader.load("tree.hbs")?)?;
+    reg.register_template_string("tree_node", loader.load("tree_node.hbs")?)?;
+    for (id, _) in REPORTS {
+        reg.register_template_string(id, loader.load(&format!("{}.hbs", id))?)?;
+    }
+    Ok(reg)
+}
+
+fn render_report_pages(
+    reg: &Handlebars,
+    data: &Map<String, Json>,
+) -> Result<Vec<ReportPage>, Box<dyn Error>> {
+    let mut pages = Vec::new();
+    for (id, title) in REPORTS {
+        pages.push(ReportPage {
+            id: id.to_string(),
+            title: title.to_string(),
+            file: format!("{}.html", id),
+            content: reg.render(id, data)?,
+            nominal: NOMINAL_REPORTS.contains(id),
+        });
+    }
+    Ok(pages)
+}
+
+fn write_page(
+    reg: &Handlebars,
+    data: &Map<String, Json>,
+    title: &str,
+    file: &str,
+    content: String,
+    output_dir: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let mut page_data = data.clone();
+    page_data.insert("title".to_string(), to_json(title));
+    page_data.insert("file".to_string(), to_json(file));
+    page_data.insert("content".to_string(), to_json(content));
+    reg.render_to_write("page", &page_data, File::create(output_dir.join(file))?)?;
+    Ok(())
+}
+
+fn get_style(loader: &TemplateLoader) -> Result<String, Box<dyn Error>> {
+    let mut style = include_str!("templates/charts/nv.d3.css").to_owned();
+    style.push('\n');
+    style.push_str(&loader.load("main.css")?);
+    Ok(style)
+}
+
+fn get_script() -> String {
+    let mut script = include_str!("templates/charts/d3.v3.js").to_owned();
+    script.push('\n');
+    script.push_str(include_str!("templates/charts/nv.d3.js"));
+    script.push('\n');
+    script.push_str(include_str!("templates/main.js"));
+    script
+}
@@ -1,5 +1,9 @@
-use crate::configuration::ReportParameters;
+use crate::configuration::{Configuration, Register, ReportParameters};
+use crate::date_utils::last_day_in_month;
+use crate::input_data::InputData;
+use crate::price_index::PriceIndex;
 use crate::report_data::*;
+use chrono::NaiveDate;
 use handlebars::to_json;
 use ledger_utils::balance::Balance;
 use ledger_utils::monthly_report::*;
@@ -7,15 +11,12 @@ use ledger_utils::prices::Prices;
 use ledger_utils::simplified_ledger;
 use serde_json::value::{Map, Value as Json};
 
-pub fn make_report_data(
-    ledger: &simplified_ledger::Ledger,
-    prices: &Prices,
-    report_params: &ReportParameters,
-) -> Map<String, Json> {
+pub fn make_report_data(input_data: &InputData, configuration: &Configuration) -> Map<String, Json> {
+    let ledger = &input_data.ledger;
+    let prices = &input_data.prices;
+    let report_params = &configuration.report_params;
     let mut data = Map::new();
 
-    configure_html_header(&mut data);
-
     let monthly_report = MonthlyReport::from(ledger);
 
     let empty_balance = Balance::new();
@@ -23,11 +24,42 @@ pub fn make_report_data(
         .monthly_balances
         .last()
         .map(|mb| &mb.total)
-        .unwrap_or_else(|| &empty_balance);
-    let summary_tree = get_summary_tree(total_balance, &prices, &report_params);
+        .unwrap_or(&empty_balance);
+    let summary_tree = get_summary_tree(total_balance, prices, report_params);
     data.insert("summary_tree".to_string(), to_json(&summary_tree));
 
-    let monthly_table = get_monthly_table(&monthly_report, &prices, &report_params);
+    let mut monthly_table = get_monthly_table(&monthly_report, prices, report_params);
+
+    let attribution_report =
+        get_attribution_report(&monthly_report, &monthly_table, prices, report_params);
+    data.insert(
+        "attribution_report".to_string(),
+        to_json(&attribution_report),
+    );
+
+    let mut real_values = None;
+    if let (Some(price_index), true) = (
+        &input_data.price_index,
+        configuration.inflation.real_values,
+    ) {
+        let base_date = match configuration.inflation.base_month {
+            Some(base_month) => Some(last_day_in_month(base_month.year, base_month.month)),
+            None => monthly_table.rows.last().map(|row| row.date),
+        };
+        if let Some(base_date) = base_date {
+            monthly_table.convert_to_real_values(price_index, base_date, report_params);
+            data.insert(
+                "real_values_base".to_string(),
+                to_json(base_date.format("%Y/%m").to_string()),
+            );
+            if let Some(warning) = get_real_values_warning(price_index, base_date, &monthly_table)
+            {
+                data.insert("real_values_warning".to_string(), to_json(warning));
+            }
+            real_values = Some((price_index, base_date));
+        }
+    }
+    data.insert("monthly_table".to_string(), to_json(&monthly_table));
 
     let assets_table = get_assets_table(&monthly_table);
     data.insert("assets_table".to_string(), to_json(&assets_table));
@@ -35,23 +67,238 @@ pub fn make_report_data(
     let assets_chart = get_assets_chart(&monthly_table);
     data.insert("assets_chart".to_string(), to_json(&assets_chart));
 
-    let expenses_chart = get_expenses_chart(&monthly_table);
+    let net_worth_chart = get_net_worth_chart(&monthly_table);
+    data.insert("net_worth_chart".to_string(), to_json(&net_worth_chart));
+
+    let net_worth_table = get_net_worth_table(
+        &monthly_report,
+        &monthly_table,
+        prices,
+        real_values,
+        report_params,
+    );
+    data.insert("net_worth_table".to_string(), to_json(&net_worth_table));
+
+    let expenses_chart = get_expenses_chart(&monthly_table, report_params);
     data.insert("expenses_chart".to_string(), to_json(&expenses_chart));
 
+    let mut monthly_expenses = get_monthly_expenses(&monthly_report, prices, report_params);
+    if let Some((price_index, base_date)) = real_values {
+        convert_expenses_to_real_values(
+            &mut monthly_expenses,
+            price_index,
+            base_date,
+            report_params,
+        );
+    }
+    let anomaly_report = get_anomaly_report(
+        ledger,
+        &monthly_expenses,
+        prices,
+        &configuration.anomalies,
+        report_params,
+    );
+    let expense_breakdowns =
+        get_expense_breakdowns(&monthly_expenses, &anomaly_report.months, report_params);
+    data.insert(
+        "expense_breakdowns".to_string(),
+        to_json(&expense_breakdowns),
+    );
+    data.insert("anomaly_report".to_string(), to_json(&anomaly_report));
+
+    let budget_report = get_budget_report(
+        &monthly_report,
+        prices,
+        &configuration.budget,
+        report_params,
+    );
+    data.insert("budget_report".to_string(), to_json(&budget_report));
+
+    let payee_report = get_payee_report(ledger, prices, &configuration.payees, report_params);
+    data.insert("payee_report".to_string(), to_json(&payee_report));
+
+    let tag_report = get_tag_report(ledger, prices, &configuration.tags, report_params);
+    data.insert("tag_report".to_string(), to_json(&tag_report));
+
+    let recurring_report =
+        get_recurring_report(ledger, prices, &configuration.payees, report_params);
+    data.insert("recurring_report".to_string(), to_json(&recurring_report));
+
+    let income_chart = get_income_chart(&monthly_table, report_params);
+    data.insert("income_chart".to_string(), to_json(&income_chart));
+
+    let income_table = get_income_table(&monthly_table);
+    data.insert("income_table".to_string(), to_json(&income_table));
+
+    let savings_rows = get_savings_rows(&monthly_table, report_params);
+    let savings_chart = get_savings_chart(&savings_rows);
+    data.insert("savings_chart".to_string(), to_json(&savings_chart));
+
+    let savings_cash_flow_chart = get_savings_cash_flow_chart(&savings_rows);
+    data.insert(
+        "savings_cash_flow_chart".to_string(),
+        to_json(&savings_cash_flow_chart),
+    );
+
+    let savings_table = get_savings_table(&savings_rows);
+    data.insert("savings_table".to_string(), to_json(&savings_table));
+
+    let investment_reports = get_investment_reports(
+        ledger,
+        &monthly_report,
+        prices,
+        &input_data.posting_costs,
+        &configuration.investments,
+        report_params,
+    );
+    data.insert(
+        "investment_reports".to_string(),
+        to_json(&investment_reports),
+    );
+
+    let allocation_report = get_allocation_report(
+        total_balance,
+        prices,
+        &configuration.allocation,
+        report_params,
+    );
+    data.insert("allocation_report".to_string(), to_json(&allocation_report));
+
+    let exposure_report = get_exposure_report(&monthly_report, prices, report_params);
+    data.insert("exposure_report".to_string(), to_json(&exposure_report));
+
+    let forecast_report = get_forecast_report(
+        &monthly_table,
+        &monthly_expenses,
+        &recurring_report,
+        &configuration.forecast,
+        report_params,
+    );
+    data.insert("forecast_report".to_string(), to_json(&forecast_report));
+
+    let year_over_year_report =
+        get_year_over_year_report(&monthly_table, &monthly_expenses, report_params);
+    data.insert(
+        "year_over_year_report".to_string(),
+        to_json(&year_over_year_report),
+    );
+
+    let register = get_register(
+        ledger,
+        prices,
+        None,
+        &configuration.register,
+        report_params,
+    );
+    data.insert("register".to_string(), to_json(&register));
+
+    data.insert(
+        "reconcile_report".to_string(),
+        to_json(&input_data.reconcile_report),
+    );
+
+    let data_quality_report =
+        get_data_quality_report(ledger, prices, &configuration.data_quality, report_params);
+    data.insert(
+        "data_quality_report".to_string(),
+        to_json(&data_quality_report),
+    );
+
+    let valuation_trace = get_valuation_trace(
+        &monthly_report,
+        prices,
+        &input_data.price_sources,
+        report_params,
+    );
+    data.insert("valuation_trace".to_string(), to_json(&valuation_trace));
+
     data
 }
 
-fn configure_html_header(data: &mut Map<String, Json>) {
-    let mut style = include_str!("../templates/charts/nv.d3.css").to_owned();
-    style.push_str("\n");
-    style.push_str(include_str!("../templates/main.css"));
+/// Months without the price index value are not adjusted.
+fn get_real_values_warning(
+    price_index: &PriceIndex,
+    base_date: NaiveDate,
+    monthly_table: &MonthlyTable,
+) -> Option<String> {
+    let start_date = price_index.get_start_date();
+    if start_date.is_none_or(|start_date| start_date > base_date) {
+        return Some("No price index for the base month, values are not adjusted".to_string());
+    }
+    let first_adjusted = monthly_table
+        .rows
+        .iter()
+        .find(|row| price_index.get_value(row.date).is_some())?;
+    if first_adjusted.date != monthly_table.rows[0].date {
+        Some(format!(
+            "No price index before {}, earlier months are not adjusted",
+            first_adjusted.date.format("%Y/%m")
+        ))
+    } else {
+        None
+    }
+}
+
+/// Data for the drill-down pages, one page per account visible in the summary tree.
+pub fn make_account_report_data(
+    ledger: &simplified_ledger::Ledger,
+    prices: &Prices,
+    register: &Register,
+    report_params: &ReportParameters,
+) -> Vec<Map<String, Json>> {
+    let monthly_report = MonthlyReport::from(ledger);
+
+    let empty_balance = Balance::new();
+    let total_balance = &monthly_report
+        .monthly_balances
+        .last()
+        .map(|mb| &mb.total)
+        .unwrap_or(&empty_balance);
+    let summary_tree = get_summary_tree(total_balance, prices, report_params);
+
+    let mut accounts = Vec::new();
+    collect_accounts(&summary_tree, &mut accounts);
 
-    let mut script = include_str!("../templates/charts/d3.v3.js").to_owned();
-    script.push_str("\n");
-    script.push_str(include_str!("../templates/charts/nv.d3.js"));
-    script.push_str("\n");
-    script.push_str(include_str!("../templates/main.js"));
+    accounts
+        .into_iter()
+        .map(|account| {
+            let mut data = Map::new();
+
+            let account_tree = find_tree_node(&summary_tree, &account);
+            data.insert("account_tree".to_string(), to_json(account_tree));
+
+            let account_rows = get_account_rows(&monthly_report, &account, prices, report_params);
+            data.insert(
+                "account_table".to_string(),
+                to_json(get_account_table(&account, &account_rows)),
+            );
+            data.insert(
+                "account_chart".to_string(),
+                to_json(get_account_chart(&account_rows)),
+            );
+
+            data.insert(
+                "account_register".to_string(),
+                to_json(get_register(
+                    ledger,
+                    prices,
+                    Some(&account),
+                    register,
+                    report_params,
+                )),
+            );
+
+            data.insert("account".to_string(), to_json(&account));
+            data
+        })
+        .collect()
+}
 
-    data.insert("html_style".to_string(), to_json(style));
-    data.insert("html_script".to_string(), to_json(script));
+fn collect_accounts(node: &TreeNode, accounts: &mut Vec<String>) {
+    if !node.account.is_empty() {
+        accounts.push(node.account.clone());
+    }
+    for child in &node.children {
+        collect_accounts(child, accounts);
+    }
 }
@@ -9,3 +9,5 @@ pub use structures::*;
 
 mod reports;
 pub use reports::*;
+
+mod time_series;
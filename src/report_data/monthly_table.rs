@@ -1,15 +1,22 @@
 use crate::configuration::{ReportParameters, VecDeref};
 use crate::date_utils::last_day_in_month;
+use crate::price_index::PriceIndex;
 use chrono::NaiveDate;
+use ledger_utils::account_balance::AccountBalance;
 use ledger_utils::balance::Balance;
 use ledger_utils::monthly_report::MonthlyReport;
-use ledger_utils::prices::Prices;
+use ledger_utils::prices::{CommoditiesPair, Prices};
+use ledger_utils::Amount;
+use rust_decimal::prelude::Zero;
 use rust_decimal::{Decimal, RoundingStrategy};
+use serde::Serialize;
 
+#[derive(Serialize)]
 pub struct MonthlyTable {
     pub rows: Vec<MonthlyRow>,
 }
 
+#[derive(Serialize)]
 pub struct MonthlyRow {
     pub date: NaiveDate,
     pub assets_total_net: Decimal,
@@ -17,12 +24,82 @@ pub struct MonthlyRow {
     pub fixed_assets: Decimal,
     pub high_risk_assets_net: Decimal,
     pub high_risk_assets_tax: Decimal,
+    /// Amount owed (liability accounts have negative balances).
+    pub liabilities: Decimal,
+    /// Assets total net minus liabilities.
+    pub net_worth: Decimal,
     pub income: Decimal,
     pub job_income: Decimal,
     pub investment_income: Decimal,
     pub expenses: Decimal,
 }
 
+impl MonthlyTable {
+    /// Changes of a cumulative value (e.g. income or expenses) month by month.
+    pub fn get_monthly_changes<F>(&self, value: F) -> Vec<Decimal>
+    where
+        F: Fn(&MonthlyRow) -> Decimal,
+    {
+        let mut previous = Decimal::zero();
+        self.rows
+            .iter()
+            .map(|row| {
+                let current = value(row);
+                let change = current - previous;
+                previous = current;
+                change
+            })
+            .collect()
+    }
+
+    /// Converts the values into prices of the base date.
+    /// Balances are converted with the price index of their month,
+    /// cumulative values (income, expenses) are sums of the converted monthly changes.
+    pub fn convert_to_real_values(
+        &mut self,
+        price_index: &PriceIndex,
+        base_date: NaiveDate,
+        params: &ReportParameters,
+    ) {
+        let income = self.get_monthly_changes(|row| row.income);
+        let job_income = self.get_monthly_changes(|row| row.job_income);
+        let investment_income = self.get_monthly_changes(|row| row.investment_income);
+        let expenses = self.get_monthly_changes(|row| row.expenses);
+        let round = |value: Decimal| {
+            value.round_dp_with_strategy(
+                params.main_commodity_decimal_points,
+                RoundingStrategy::MidpointAwayFromZero,
+            )
+        };
+
+        let mut previous: Option<(Decimal, Decimal, Decimal, Decimal)> = None;
+        for (pos, row) in self.rows.iter_mut().enumerate() {
+            let factor = price_index.get_real_value_factor(row.date, base_date);
+            row.assets_total_net = round(row.assets_total_net * factor);
+            row.liquid_assets = round(row.liquid_assets * factor);
+            row.fixed_assets = round(row.fixed_assets * factor);
+            row.high_risk_assets_net = round(row.high_risk_assets_net * factor);
+            row.high_risk_assets_tax = round(row.high_risk_assets_tax * factor);
+            row.liabilities = round(row.liabilities * factor);
+            row.net_worth = row.assets_total_net - row.liabilities;
+
+            let (income_total, job_income_total, investment_income_total, expenses_total) =
+                previous.unwrap_or_default();
+            row.income = income_total + round(income[pos] * factor);
+            row.job_income = job_income_total + round(job_income[pos] * factor);
+            row.investment_income =
+                investment_income_total + round(investment_income[pos] * factor);
+            row.expenses = expenses_total + round(expenses[pos] * factor);
+            previous = Some((
+                row.income,
+                row.job_income,
+                row.investment_income,
+                row.expenses,
+            ));
+        }
+    }
+}
+
 pub fn get_monthly_table(
     monthly_report: &MonthlyReport,
     prices: &Prices,
@@ -33,9 +110,9 @@ pub fn get_monthly_table(
     for monthly_balance in &monthly_report.monthly_balances {
         let last_day = last_day_in_month(monthly_balance.year, monthly_balance.month);
 
-        let calc = MonthlyCalculator::new(&monthly_balance.total, &prices, last_day, &params);
+        let calc = MonthlyCalculator::new(&monthly_balance.total, prices, last_day, params);
 
-        let tax = Decimal::new(32, 2);
+        let tax = get_high_risk_tax();
 
         let assets_liquid = calc.get_value(&params.assets_liquid);
         let assets_fixed = calc.get_value(&params.assets_fixed);
@@ -50,18 +127,23 @@ pub fn get_monthly_table(
                 params.main_commodity_decimal_points,
                 RoundingStrategy::MidpointAwayFromZero,
             );
+        let liabilities = -calc.get_value(&params.liabilities);
         let income = calc.get_value(&params.income);
         let job_income = calc.get_value(&params.job_income);
         let investment_income = calc.get_value(&params.investment_income);
         let expenses = calc.get_value(&params.expenses);
 
+        let assets_total_net = assets_liquid + assets_fixed + assets_high_risk_net;
+
         rows.push(MonthlyRow {
             date: last_day,
-            assets_total_net: assets_liquid + assets_fixed + assets_high_risk_net,
+            assets_total_net,
             liquid_assets: assets_liquid,
             fixed_assets: assets_fixed,
             high_risk_assets_net: assets_high_risk_net,
             high_risk_assets_tax: assets_high_risk_tax,
+            liabilities,
+            net_worth: assets_total_net - liabilities,
             income,
             job_income,
             investment_income,
@@ -72,7 +154,12 @@ pub fn get_monthly_table(
     MonthlyTable { rows }
 }
 
-struct MonthlyCalculator<'a> {
+/// Tax paid when the high risk assets are sold (part of their value).
+pub fn get_high_risk_tax() -> Decimal {
+    Decimal::new(32, 2)
+}
+
+pub struct MonthlyCalculator<'a> {
     balance: &'a Balance,
     prices: &'a Prices,
     last_day: NaiveDate,
@@ -94,14 +181,78 @@ impl<'a> MonthlyCalculator<'a> {
         }
     }
 
-    fn get_value(&self, accounts: &Vec<String>) -> Decimal {
-        self.balance
-            .get_account_balance(&(accounts.as_deref()))
-            .value_in_commodity_rounded(
-                &self.params.main_commodity,
-                self.params.main_commodity_decimal_points,
-                self.last_day,
-                &self.prices,
-            )
+    pub fn get_value(&self, accounts: &[String]) -> Decimal {
+        get_balance_value(
+            &self.balance.get_account_balance(&(accounts.as_deref())),
+            self.last_day,
+            self.prices,
+            self.params,
+        )
     }
+
+    /// Value of the account and its sub-accounts
+    /// (unlike `get_value`, `Expenses:Food` does not include `Expenses:FoodX`).
+    pub fn get_account_value(&self, account: &str) -> Decimal {
+        let sub_account_prefix = format!("{}:", account);
+        let mut account_balance = AccountBalance::new();
+        for (name, balance) in &self.balance.account_balances {
+            if name == account || name.starts_with(&sub_account_prefix) {
+                account_balance += balance;
+            }
+        }
+        get_balance_value(&account_balance, self.last_day, self.prices, self.params)
+    }
+}
+
+/// Value of the amount in the main commodity.
+pub fn get_amount_value(
+    amount: &Amount,
+    date: NaiveDate,
+    prices: &Prices,
+    params: &ReportParameters,
+) -> Decimal {
+    let mut balance = AccountBalance::new();
+    balance += amount;
+    get_balance_value(&balance, date, prices, params)
+}
+
+/// Price of the commodity in the main commodity and its date,
+/// the latest price not after the date (as `Prices::get_rate`).
+pub fn get_price(
+    commodity: &str,
+    date: NaiveDate,
+    prices: &Prices,
+    params: &ReportParameters,
+) -> Option<(NaiveDate, Decimal)> {
+    prices
+        .rates
+        .get(&CommoditiesPair::new(commodity, &params.main_commodity))
+        .and_then(|rates| rates.table.range(..=date).next_back())
+        .map(|(price_date, price)| (*price_date, *price))
+}
+
+/// Value of the balance in the main commodity.
+/// Amounts without a price are not counted (see the valuation trace).
+pub fn get_balance_value(
+    balance: &AccountBalance,
+    date: NaiveDate,
+    prices: &Prices,
+    params: &ReportParameters,
+) -> Decimal {
+    balance
+        .amounts
+        .values()
+        .filter_map(|amount| {
+            if amount.commodity.name == params.main_commodity {
+                Some(amount.quantity)
+            } else {
+                get_price(&amount.commodity.name, date, prices, params)
+                    .map(|(_, price)| amount.quantity * price)
+            }
+        })
+        .sum::<Decimal>()
+        .round_dp_with_strategy(
+            params.main_commodity_decimal_points,
+            RoundingStrategy::MidpointAwayFromZero,
+        )
 }
@@ -0,0 +1,113 @@
+use crate::configuration::ReportParameters;
+use crate::date_utils::last_day_in_month;
+use crate::report_data::monthly_table::get_balance_value;
+use crate::report_data::structures::{LineChart, LineChartSerie, Table, TableCell, TableRow};
+use chrono::{Datelike, NaiveDate};
+use handlebars::to_json;
+use ledger_utils::account_balance::AccountBalance;
+use ledger_utils::monthly_report::MonthlyReport;
+use ledger_utils::prices::Prices;
+use rust_decimal::prelude::{ToPrimitive, Zero};
+use rust_decimal::Decimal;
+
+pub struct AccountRow {
+    pub date: NaiveDate,
+    pub balance: Decimal,
+    pub change: Decimal,
+}
+
+/// Monthly balance of a particular account (including its sub-accounts)
+/// in the main commodity.
+pub fn get_account_rows(
+    monthly_report: &MonthlyReport,
+    account: &str,
+    prices: &Prices,
+    params: &ReportParameters,
+) -> Vec<AccountRow> {
+    let sub_account_prefix = format!("{}:", account);
+    let mut rows: Vec<AccountRow> = Vec::new();
+
+    for monthly_balance in &monthly_report.monthly_balances {
+        let last_day = last_day_in_month(monthly_balance.year, monthly_balance.month);
+
+        let mut account_balance = AccountBalance::new();
+        for (name, balance) in &monthly_balance.total.account_balances {
+            if name == account || name.starts_with(&sub_account_prefix) {
+                account_balance += balance;
+            }
+        }
+
+        let balance = get_balance_value(&account_balance, last_day, prices, params);
+        let previous_balance = rows.last().map(|r| r.balance).unwrap_or_else(Decimal::zero);
+
+        rows.push(AccountRow {
+            date: last_day,
+            balance,
+            change: balance - previous_balance,
+        });
+    }
+
+    rows
+}
+
+/// Monthly changes are linked to the postings in the register.
+pub fn get_account_table(account: &str, rows: &[AccountRow]) -> Table {
+    let headers = vec![
+        "Date".to_string(),
+        "Balance".to_string(),
+        "Monthly Change".to_string(),
+    ];
+
+    let rows = rows
+        .iter()
+        .map(|row| TableRow {
+            columns: vec![
+                TableCell::Month {
+                    year: row.date.year(),
+                    month: row.date.month(),
+                },
+                TableCell::Value(row.balance),
+                TableCell::Register {
+                    cell: Box::new(TableCell::Value(row.change)),
+                    account: account.to_string(),
+                    month: Some(row.date.format("%Y/%m").to_string()),
+                },
+            ],
+        })
+        .collect();
+
+    Table { headers, rows }
+}
+
+pub fn get_account_chart(rows: &[AccountRow]) -> LineChart {
+    let min_date = rows[0].date.and_hms(0, 0, 0).timestamp_millis();
+    let max_date = rows
+        .last()
+        .unwrap()
+        .date
+        .and_hms(0, 0, 0)
+        .timestamp_millis();
+
+    let series_balance = rows
+        .iter()
+        .map(|row| {
+            [
+                row.date.and_hms(0, 0, 0).timestamp_millis() as f64,
+                row.balance.to_f64().unwrap(),
+            ]
+        })
+        .collect();
+
+    LineChart {
+        id: "accountChart".to_string(),
+        min_x: min_date as f64,
+        max_x: max_date as f64,
+        digit_points: 0,
+        series: to_json(vec![LineChartSerie {
+            key: "Balance".to_string(),
+            area: true,
+            values: series_balance,
+        }])
+        .to_string(),
+    }
+}
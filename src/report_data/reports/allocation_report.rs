@@ -0,0 +1,138 @@
+use crate::configuration::{Allocation, AssetClass, ReportParameters};
+use crate::report_data::monthly_table::MonthlyCalculator;
+use chrono::Local;
+use ledger_utils::balance::Balance;
+use ledger_utils::prices::Prices;
+use rust_decimal::prelude::Zero;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+/// Current holdings grouped into asset classes, compared with the target allocation.
+#[derive(Serialize)]
+pub struct AllocationReport {
+    pub main_commodity: String,
+    /// New money invested while rebalancing.
+    pub contribution: Decimal,
+    pub rows: Vec<AllocationRow>,
+    pub total: AllocationRow,
+}
+
+#[derive(Serialize)]
+pub struct AllocationRow {
+    pub name: String,
+    pub value: Decimal,
+    /// Percent of the classes with targets (same base as the targets),
+    /// for classes without target percent of the total.
+    pub actual_percent: Decimal,
+    pub target_percent: Option<Decimal>,
+    /// Actual minus target percent.
+    pub drift: Option<Decimal>,
+    pub target_value: Option<Decimal>,
+    /// Value to buy (positive) or sell (negative) to reach the target.
+    pub trade: Option<Decimal>,
+}
+
+pub fn get_allocation_report(
+    total_balance: &Balance,
+    prices: &Prices,
+    allocation: &Allocation,
+    params: &ReportParameters,
+) -> AllocationReport {
+    let classes = if allocation.classes.is_empty() {
+        get_default_classes(params)
+    } else {
+        allocation.classes.clone()
+    };
+
+    let calc = MonthlyCalculator::new(
+        total_balance,
+        prices,
+        Local::now().date().naive_local(),
+        params,
+    );
+    let values: Vec<Decimal> = classes
+        .iter()
+        .map(|class| calc.get_value(&class.accounts))
+        .collect();
+    let total_value: Decimal = values.iter().sum();
+
+    // classes without target are left as they are
+    let targets_sum: Decimal = classes.iter().filter_map(|class| class.target).sum();
+    let targeted_value: Decimal = classes
+        .iter()
+        .zip(&values)
+        .filter(|(class, _)| class.target.is_some())
+        .map(|(_, value)| *value)
+        .sum();
+    let rebalanced_value = targeted_value + allocation.contribution;
+
+    let rows = classes
+        .iter()
+        .zip(&values)
+        .map(|(class, value)| {
+            let target_percent = class.target;
+            let actual_percent = if target_percent.is_some() && !targeted_value.is_zero() {
+                // targets do not have to sum up to 100
+                (*value * targets_sum / targeted_value).round_dp(1)
+            } else {
+                get_percent(*value, total_value)
+            };
+            let target_value = target_percent
+                .filter(|_| !targets_sum.is_zero())
+                .map(|target| (rebalanced_value * target / targets_sum).round_dp(2));
+            AllocationRow {
+                name: class.name.clone(),
+                value: *value,
+                actual_percent,
+                target_percent,
+                drift: target_percent.map(|target| actual_percent - target),
+                target_value,
+                trade: target_value.map(|target_value| target_value - *value),
+            }
+        })
+        .collect();
+
+    AllocationReport {
+        main_commodity: params.main_commodity.clone(),
+        contribution: allocation.contribution,
+        rows,
+        total: AllocationRow {
+            name: "Total".to_string(),
+            value: total_value,
+            actual_percent: get_percent(total_value, total_value),
+            target_percent: Some(targets_sum).filter(|sum| !sum.is_zero()),
+            drift: None,
+            target_value: None,
+            trade: Some(allocation.contribution).filter(|c| !c.is_zero()),
+        },
+    }
+}
+
+/// Liquid, fixed and high risk assets (without targets).
+fn get_default_classes(params: &ReportParameters) -> Vec<AssetClass> {
+    vec![
+        AssetClass {
+            name: "Liquid Assets".to_string(),
+            accounts: params.assets_liquid.clone(),
+            target: None,
+        },
+        AssetClass {
+            name: "Fixed Assets".to_string(),
+            accounts: params.assets_fixed.clone(),
+            target: None,
+        },
+        AssetClass {
+            name: "High Risk Assets".to_string(),
+            accounts: params.assets_high_risk.clone(),
+            target: None,
+        },
+    ]
+}
+
+fn get_percent(value: Decimal, total: Decimal) -> Decimal {
+    if total.is_zero() {
+        Decimal::zero()
+    } else {
+        (value * Decimal::new(100, 0) / total).round_dp(1)
+    }
+}
@@ -0,0 +1,235 @@
+use crate::configuration::{Anomalies, ReportParameters};
+use crate::report_data::monthly_table::get_amount_value;
+use crate::report_data::reports::{get_expense_category, MonthlyExpenses};
+use chrono::{Datelike, NaiveDate};
+use ledger_utils::prices::Prices;
+use ledger_utils::simplified_ledger;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive, Zero};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Unusual months and transactions per expense category.
+#[derive(Serialize)]
+pub struct AnomalyReport {
+    pub main_commodity: String,
+    /// The latest months first.
+    pub months: Vec<MonthlyAnomaly>,
+    /// The latest transactions first.
+    pub transactions: Vec<LargeTransaction>,
+}
+
+#[derive(Serialize)]
+pub struct MonthlyAnomaly {
+    /// The last day of the month.
+    pub date: NaiveDate,
+    pub category: String,
+    pub value: Decimal,
+    pub trailing_mean: Decimal,
+    pub std_deviation: Decimal,
+    /// The biggest transactions of the category in the month.
+    pub transactions: Vec<AnomalyTransaction>,
+}
+
+#[derive(Serialize)]
+pub struct LargeTransaction {
+    pub transaction: AnomalyTransaction,
+    pub category: String,
+    /// Average of the previous transactions of the category.
+    pub category_average: Decimal,
+}
+
+#[derive(Serialize, Clone)]
+pub struct AnomalyTransaction {
+    pub date: NaiveDate,
+    pub description: String,
+    pub value: Decimal,
+}
+
+/// Number of the previous months (or transactions) needed to flag a category.
+const MIN_HISTORY: usize = 3;
+
+/// Number of the transactions listed for an unusual month.
+const MAX_TRANSACTIONS: usize = 5;
+
+pub fn get_anomaly_report(
+    ledger: &simplified_ledger::Ledger,
+    monthly_expenses: &[MonthlyExpenses],
+    prices: &Prices,
+    anomalies: &Anomalies,
+    params: &ReportParameters,
+) -> AnomalyReport {
+    let transactions = get_category_transactions(ledger, prices, params);
+
+    let mut months = get_monthly_anomalies(monthly_expenses, &transactions, anomalies, params);
+    months.sort_by(|m1, m2| m2.date.cmp(&m1.date).then(m1.category.cmp(&m2.category)));
+
+    let mut large_transactions = get_large_transactions(&transactions, anomalies);
+    large_transactions.sort_by_key(|t| Reverse(t.transaction.date));
+
+    AnomalyReport {
+        main_commodity: params.main_commodity.clone(),
+        months,
+        transactions: large_transactions,
+    }
+}
+
+/// Values of the transactions by expense category (in order of the ledger).
+fn get_category_transactions(
+    ledger: &simplified_ledger::Ledger,
+    prices: &Prices,
+    params: &ReportParameters,
+) -> BTreeMap<String, Vec<AnomalyTransaction>> {
+    let mut category_transactions: BTreeMap<String, Vec<AnomalyTransaction>> = BTreeMap::new();
+    for transaction in &ledger.transactions {
+        let mut values: BTreeMap<String, Decimal> = BTreeMap::new();
+        for posting in &transaction.postings {
+            if let Some(category) = get_expense_category(&posting.account, params) {
+                *values.entry(category).or_insert_with(Decimal::zero) +=
+                    get_amount_value(&posting.amount, transaction.date, prices, params);
+            }
+        }
+        for (category, value) in values {
+            category_transactions
+                .entry(category)
+                .or_default()
+                .push(AnomalyTransaction {
+                    date: transaction.date,
+                    description: transaction.description.clone(),
+                    value,
+                });
+        }
+    }
+    category_transactions
+}
+
+/// Months with expenses of a category above the trailing mean
+/// by more than the configured number of standard deviations
+/// and by more than the minimal increase (e.g. with constant history the deviation is zero).
+fn get_monthly_anomalies(
+    monthly_expenses: &[MonthlyExpenses],
+    transactions: &BTreeMap<String, Vec<AnomalyTransaction>>,
+    anomalies: &Anomalies,
+    params: &ReportParameters,
+) -> Vec<MonthlyAnomaly> {
+    let categories: BTreeSet<String> = monthly_expenses
+        .iter()
+        .flat_map(|month| month.accounts.keys())
+        .filter_map(|account| get_expense_category(account, params))
+        .collect();
+
+    let mut result = Vec::new();
+    for category in categories {
+        let category_settings = anomalies.categories.get(&category);
+        if category_settings.is_some_and(|settings| settings.ignore) {
+            continue;
+        }
+        let std_deviations = category_settings
+            .and_then(|settings| settings.std_deviations)
+            .unwrap_or(anomalies.std_deviations);
+
+        let values: Vec<Decimal> = monthly_expenses
+            .iter()
+            .map(|month| {
+                month
+                    .accounts
+                    .iter()
+                    .filter(|(account, _)| {
+                        get_expense_category(account, params).as_ref() == Some(&category)
+                    })
+                    .map(|(_, value)| *value)
+                    .sum()
+            })
+            .collect();
+
+        for (pos, month) in monthly_expenses.iter().enumerate() {
+            // shorter windows would never have enough history
+            let trailing_months = anomalies.trailing_months.max(MIN_HISTORY);
+            let trailing = &values[pos.saturating_sub(trailing_months)..pos];
+            if trailing.len() < MIN_HISTORY {
+                continue;
+            }
+            let (mean, std_deviation) = get_mean_and_std_deviation(trailing);
+            let increase = values[pos] - mean;
+            if increase <= std_deviation * std_deviations
+                || increase * Decimal::new(100, 0) <= mean.abs() * anomalies.min_increase_percent
+            {
+                continue;
+            }
+
+            // categories with zero-sum postings only have no transactions
+            let mut month_transactions: Vec<AnomalyTransaction> = transactions
+                .get(&category)
+                .into_iter()
+                .flatten()
+                .filter(|t| {
+                    t.date.year() == month.date.year() && t.date.month() == month.date.month()
+                })
+                .cloned()
+                .collect();
+            month_transactions.sort_by_key(|t| Reverse(t.value));
+            month_transactions.truncate(MAX_TRANSACTIONS);
+
+            result.push(MonthlyAnomaly {
+                date: month.date,
+                category: category.clone(),
+                value: values[pos],
+                trailing_mean: mean.round_dp(params.main_commodity_decimal_points),
+                std_deviation: std_deviation.round_dp(params.main_commodity_decimal_points),
+                transactions: month_transactions,
+            });
+        }
+    }
+    result
+}
+
+/// Transactions larger than the average previous transaction of the category
+/// multiplied by the configured factor.
+fn get_large_transactions(
+    transactions: &BTreeMap<String, Vec<AnomalyTransaction>>,
+    anomalies: &Anomalies,
+) -> Vec<LargeTransaction> {
+    let mut result = Vec::new();
+    for (category, transactions) in transactions {
+        let category_settings = anomalies.categories.get(category);
+        if category_settings.is_some_and(|settings| settings.ignore) {
+            continue;
+        }
+        let factor = category_settings
+            .and_then(|settings| settings.large_transaction)
+            .unwrap_or(anomalies.large_transaction);
+
+        let mut total = Decimal::zero();
+        for (pos, transaction) in transactions.iter().enumerate() {
+            if pos >= MIN_HISTORY {
+                let average = total / Decimal::from(pos);
+                if average > Decimal::zero() && transaction.value > average * factor {
+                    result.push(LargeTransaction {
+                        transaction: transaction.clone(),
+                        category: category.clone(),
+                        category_average: average.round_dp(2),
+                    });
+                }
+            }
+            total += transaction.value;
+        }
+    }
+    result
+}
+
+/// Population standard deviation.
+fn get_mean_and_std_deviation(values: &[Decimal]) -> (Decimal, Decimal) {
+    let count = Decimal::from(values.len());
+    let mean = values.iter().sum::<Decimal>() / count;
+    let variance = values
+        .iter()
+        .map(|value| (*value - mean) * (*value - mean))
+        .sum::<Decimal>()
+        / count;
+    let std_deviation = variance
+        .to_f64()
+        .and_then(|variance| Decimal::from_f64(variance.sqrt()))
+        .unwrap_or_else(Decimal::zero);
+    (mean, std_deviation)
+}
@@ -0,0 +1,264 @@
+use crate::configuration::ReportParameters;
+use crate::date_utils::last_day_in_month;
+use crate::report_data::monthly_table::{
+    get_balance_value, get_high_risk_tax, MonthlyCalculator, MonthlyTable,
+};
+use crate::report_data::structures::{
+    BarChart, BarChartSerie, Table, TableCell, TableRow, WaterfallChart, WaterfallStep,
+};
+use chrono::{Datelike, NaiveDate};
+use handlebars::to_json;
+use ledger_utils::account_balance::AccountBalance;
+use ledger_utils::monthly_report::MonthlyReport;
+use ledger_utils::prices::Prices;
+use rust_decimal::prelude::{ToPrimitive, Zero};
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+/// Monthly changes of the assets total net split into their sources.
+#[derive(Serialize)]
+pub struct AttributionReport {
+    /// Change of the last month.
+    pub waterfall_chart: WaterfallChart,
+    pub chart: BarChart,
+    pub table: Table,
+}
+
+struct AttributionMonth {
+    date: NaiveDate,
+    start: Decimal,
+    end: Decimal,
+    /// Income minus expenses.
+    net_contributions: Decimal,
+    /// Changes caused by the other accounts, e.g. liabilities or opening balances (equity).
+    transfers: Decimal,
+    /// Value change of the previous month holdings caused by the prices (per asset group).
+    revaluations: Vec<Decimal>,
+    /// The rest of the change, e.g. exchanges at prices different from the month end prices.
+    realized_gains: Decimal,
+}
+
+const ASSET_GROUPS: &[&str] = &["Liquid Assets", "Fixed Assets", "High Risk Assets"];
+
+/// Works with nominal values (revaluations are nominal price changes).
+pub fn get_attribution_report(
+    monthly_report: &MonthlyReport,
+    monthly_table: &MonthlyTable,
+    prices: &Prices,
+    params: &ReportParameters,
+) -> Option<AttributionReport> {
+    let months = get_attribution_months(monthly_report, monthly_table, prices, params);
+    let last_month = months.last()?;
+
+    Some(AttributionReport {
+        waterfall_chart: get_waterfall_chart(last_month),
+        chart: get_attribution_chart(&months),
+        table: get_attribution_table(&months),
+    })
+}
+
+fn get_attribution_months(
+    monthly_report: &MonthlyReport,
+    monthly_table: &MonthlyTable,
+    prices: &Prices,
+    params: &ReportParameters,
+) -> Vec<AttributionMonth> {
+    // income accounts have negative balances
+    let income = monthly_table.get_monthly_changes(|row| -row.income);
+    let expenses = monthly_table.get_monthly_changes(|row| row.expenses);
+    let groups = [
+        &params.assets_liquid,
+        &params.assets_fixed,
+        &params.assets_high_risk,
+    ];
+    let net_part = Decimal::new(1, 0) - get_high_risk_tax();
+    let is_other_account = |account: &str| {
+        !params
+            .assets
+            .iter()
+            .chain(params.income.iter())
+            .chain(params.expenses.iter())
+            .any(|prefix| account.starts_with(prefix.as_str()))
+            // currency trading accounts (see handle_foreign_currencies)
+            && !account.starts_with("Trading:")
+    };
+
+    let mut months = Vec::new();
+    for (pos, row) in monthly_table.rows.iter().enumerate() {
+        let start = if pos > 0 {
+            monthly_table.rows[pos - 1].assets_total_net
+        } else {
+            Decimal::zero()
+        };
+
+        let revaluations: Vec<Decimal> = match pos.checked_sub(1) {
+            Some(previous_pos) => {
+                let previous = &monthly_report.monthly_balances[previous_pos];
+                let previous_date = last_day_in_month(previous.year, previous.month);
+                let calc_previous =
+                    MonthlyCalculator::new(&previous.total, prices, previous_date, params);
+                let calc_current =
+                    MonthlyCalculator::new(&previous.total, prices, row.date, params);
+                groups
+                    .iter()
+                    .enumerate()
+                    .map(|(group_pos, accounts)| {
+                        let revaluation =
+                            calc_current.get_value(accounts) - calc_previous.get_value(accounts);
+                        if group_pos == 2 {
+                            (revaluation * net_part).round_dp(params.main_commodity_decimal_points)
+                        } else {
+                            revaluation
+                        }
+                    })
+                    .collect()
+            }
+            None => vec![Decimal::zero(); groups.len()],
+        };
+
+        let mut other_accounts_change = AccountBalance::new();
+        for (account, balance) in &monthly_report.monthly_balances[pos]
+            .monthly_change
+            .account_balances
+        {
+            if is_other_account(account) {
+                other_accounts_change += balance;
+            }
+        }
+        let transfers = -get_balance_value(&other_accounts_change, row.date, prices, params);
+
+        let net_contributions = income[pos] - expenses[pos];
+        let change = row.assets_total_net - start;
+        months.push(AttributionMonth {
+            date: row.date,
+            start,
+            end: row.assets_total_net,
+            net_contributions,
+            transfers,
+            realized_gains: change
+                - net_contributions
+                - transfers
+                - revaluations.iter().sum::<Decimal>(),
+            revaluations,
+        });
+    }
+
+    months
+}
+
+fn get_attribution_table(months: &[AttributionMonth]) -> Table {
+    let mut headers = vec![
+        "Date".to_string(),
+        "Start".to_string(),
+        "Net Contributions".to_string(),
+        "Transfers".to_string(),
+        "Realized Gains & Other".to_string(),
+    ];
+    for group in ASSET_GROUPS {
+        headers.push(format!("Revaluation: {}", group));
+    }
+    headers.push("End".to_string());
+
+    let rows = months
+        .iter()
+        .map(|month| {
+            let mut columns = vec![
+                TableCell::Month {
+                    year: month.date.year(),
+                    month: month.date.month(),
+                },
+                TableCell::Value(month.start),
+                TableCell::Value(month.net_contributions),
+                TableCell::Value(month.transfers),
+                TableCell::Value(month.realized_gains),
+            ];
+            for revaluation in &month.revaluations {
+                columns.push(TableCell::Value(*revaluation));
+            }
+            columns.push(TableCell::Value(month.end));
+            TableRow { columns }
+        })
+        .collect();
+
+    Table { headers, rows }
+}
+
+fn get_attribution_chart(months: &[AttributionMonth]) -> BarChart {
+    let get_serie =
+        |key: String, serie_type: &str, value: &dyn Fn(&AttributionMonth) -> Decimal| {
+            BarChartSerie {
+                key,
+                serie_type: serie_type.to_string(),
+                values: months
+                    .iter()
+                    .map(|month| {
+                        [
+                            month.date.and_hms(0, 0, 0).timestamp_millis() as f64,
+                            value(month).to_f64().unwrap(),
+                        ]
+                    })
+                    .collect(),
+            }
+        };
+
+    let mut series = vec![
+        get_serie("Net Contributions".to_string(), "bar", &|m| {
+            m.net_contributions
+        }),
+        get_serie("Transfers".to_string(), "bar", &|m| m.transfers),
+        get_serie("Realized Gains & Other".to_string(), "bar", &|m| {
+            m.realized_gains
+        }),
+    ];
+    for (pos, group) in ASSET_GROUPS.iter().enumerate() {
+        series.push(get_serie(format!("Revaluation: {}", group), "bar", &|m| {
+            m.revaluations[pos]
+        }));
+    }
+    series.push(get_serie("Total Change".to_string(), "line", &|m| {
+        m.end - m.start
+    }));
+
+    BarChart {
+        id: "attributionChart".to_string(),
+        digit_points: 0,
+        series: to_json(series).to_string(),
+    }
+}
+
+fn get_waterfall_chart(month: &AttributionMonth) -> WaterfallChart {
+    let step = |label: String, value: Decimal, is_total: bool| WaterfallStep {
+        label,
+        value: value.to_f64().unwrap(),
+        is_total,
+    };
+
+    let mut steps = vec![
+        step(
+            format!("{}/{:02} Start", month.date.year(), month.date.month()),
+            month.start,
+            true,
+        ),
+        step(
+            "Net Contributions".to_string(),
+            month.net_contributions,
+            false,
+        ),
+        step("Transfers".to_string(), month.transfers, false),
+        step(
+            "Realized Gains & Other".to_string(),
+            month.realized_gains,
+            false,
+        ),
+    ];
+    for (group, revaluation) in ASSET_GROUPS.iter().zip(&month.revaluations) {
+        steps.push(step(format!("Revaluation: {}", group), *revaluation, false));
+    }
+    steps.push(step("End".to_string(), month.end, true));
+
+    WaterfallChart {
+        id: "attributionWaterfallChart".to_string(),
+        digit_points: 0,
+        steps: to_json(steps).to_string(),
+    }
+}
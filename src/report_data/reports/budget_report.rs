@@ -0,0 +1,149 @@
+use crate::configuration::{Budget, ReportParameters};
+use crate::date_utils::last_day_in_month;
+use crate::report_data::monthly_table::MonthlyCalculator;
+use ledger_utils::monthly_report::MonthlyReport;
+use ledger_utils::prices::Prices;
+use rust_decimal::prelude::Zero;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct BudgetReport {
+    pub main_commodity: String,
+    /// The last month and the last year.
+    pub current_rows: Vec<BudgetRow>,
+    /// All the periods, the most recent first.
+    pub rows: Vec<BudgetRow>,
+}
+
+#[derive(Serialize)]
+pub struct BudgetRow {
+    pub period: String,
+    pub account: String,
+    pub budget: Decimal,
+    pub actual: Decimal,
+    pub remaining: Decimal,
+    pub used_percent: Decimal,
+    /// Expenses above the budget or income below the budget.
+    pub is_off_budget: bool,
+}
+
+pub fn get_budget_report(
+    monthly_report: &MonthlyReport,
+    prices: &Prices,
+    budget: &Budget,
+    params: &ReportParameters,
+) -> BudgetReport {
+    let mut monthly_rows: Vec<Vec<BudgetRow>> = Vec::new();
+    let mut yearly_rows: Vec<Vec<BudgetRow>> = Vec::new();
+    let mut year_to_date: Vec<Decimal> = vec![Decimal::zero(); budget.yearly.len()];
+
+    for (pos, monthly_balance) in monthly_report.monthly_balances.iter().enumerate() {
+        let last_day = last_day_in_month(monthly_balance.year, monthly_balance.month);
+        let calc =
+            MonthlyCalculator::new(&monthly_balance.monthly_change, prices, last_day, params);
+
+        monthly_rows.push(
+            budget
+                .monthly
+                .iter()
+                .map(|(account, amount)| {
+                    let period = format!("{}/{:02}", monthly_balance.year, monthly_balance.month);
+                    let actual = get_actual(&calc, account, params);
+                    get_budget_row(period, account, *amount, actual, params)
+                })
+                .collect(),
+        );
+
+        for ((account, _), value) in budget.yearly.iter().zip(year_to_date.iter_mut()) {
+            *value += get_actual(&calc, account, params);
+        }
+
+        let is_last_month_of_year = monthly_report
+            .monthly_balances
+            .get(pos + 1)
+            .map(|next| next.year != monthly_balance.year)
+            .unwrap_or(true);
+        if is_last_month_of_year {
+            yearly_rows.push(
+                budget
+                    .yearly
+                    .iter()
+                    .zip(year_to_date.iter())
+                    .map(|((account, amount), actual)| {
+                        let period = format!("{}", monthly_balance.year);
+                        get_budget_row(period, account, *amount, *actual, params)
+                    })
+                    .collect(),
+            );
+            year_to_date = vec![Decimal::zero(); budget.yearly.len()];
+        }
+    }
+
+    let mut current_rows = Vec::new();
+    let mut rows = Vec::new();
+    if let Some(last_rows) = monthly_rows.pop() {
+        current_rows.extend(last_rows);
+    }
+    if let Some(last_rows) = yearly_rows.pop() {
+        current_rows.extend(last_rows);
+    }
+    for month_rows in monthly_rows.into_iter().rev() {
+        rows.extend(month_rows);
+    }
+    for year_rows in yearly_rows.into_iter().rev() {
+        rows.extend(year_rows);
+    }
+
+    BudgetReport {
+        main_commodity: params.main_commodity.clone(),
+        current_rows,
+        rows,
+    }
+}
+
+/// Income accounts have negative balances, so their values are negated.
+fn get_actual(calc: &MonthlyCalculator, account: &str, params: &ReportParameters) -> Decimal {
+    let value = calc.get_account_value(account);
+    if is_income_account(account, params) {
+        -value
+    } else {
+        value
+    }
+}
+
+fn is_income_account(account: &str, params: &ReportParameters) -> bool {
+    params
+        .income
+        .iter()
+        .any(|prefix| account.starts_with(prefix.as_str()))
+}
+
+fn get_budget_row(
+    period: String,
+    account: &str,
+    budget: Decimal,
+    actual: Decimal,
+    params: &ReportParameters,
+) -> BudgetRow {
+    let used_percent = if budget.is_zero() {
+        Decimal::zero()
+    } else {
+        (actual * Decimal::new(100, 0) / budget).round_dp(1)
+    };
+    let is_off_budget = if is_income_account(account, params) {
+        actual < budget
+    } else {
+        actual > budget
+    };
+
+    BudgetRow {
+        period,
+        account: account.to_string(),
+        budget,
+        actual,
+        remaining: budget - actual,
+        used_percent,
+        is_off_budget,
+    }
+}
@@ -0,0 +1,302 @@
+use crate::configuration::{DataQuality, ReportParameters};
+use crate::report_data::monthly_table::get_price;
+use chrono::{Local, NaiveDate};
+use ledger_utils::prices::Prices;
+use ledger_utils::simplified_ledger;
+use ledger_utils::Amount;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Postings and prices that make the reports incomplete or wrong.
+#[derive(Serialize)]
+pub struct DataQualityReport {
+    pub issues: usize,
+    pub catch_all_postings: Vec<QualityPosting>,
+    pub missing_prices: Vec<MissingPrice>,
+    pub stale_prices: Vec<StalePrice>,
+    pub duplicates: Vec<QualityTransaction>,
+    pub future_transactions: Vec<QualityTransaction>,
+}
+
+#[derive(Serialize)]
+pub struct QualityPosting {
+    pub date: NaiveDate,
+    pub payee: String,
+    pub account: String,
+    pub amount: String,
+}
+
+#[derive(Serialize)]
+pub struct QualityTransaction {
+    pub date: NaiveDate,
+    pub payee: String,
+    pub postings: String,
+    /// Number of the same transactions.
+    pub count: usize,
+}
+
+/// Commodity held without any price in the main commodity.
+#[derive(Serialize)]
+pub struct MissingPrice {
+    pub commodity: String,
+    pub accounts: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct StalePrice {
+    pub commodity: String,
+    pub last_price_date: NaiveDate,
+    pub days: i64,
+}
+
+pub fn get_data_quality_report(
+    ledger: &simplified_ledger::Ledger,
+    prices: &Prices,
+    data_quality: &DataQuality,
+    params: &ReportParameters,
+) -> DataQualityReport {
+    // the current balances are valued at today's prices
+    let today = Local::now().date().naive_local();
+
+    let catch_all_postings = get_catch_all_postings(ledger, &data_quality.catch_all_accounts);
+    let (missing_prices, stale_prices) =
+        get_price_issues(ledger, prices, today, data_quality.stale_price_days, params);
+    let duplicates = get_duplicates(ledger, params);
+    let future_transactions = ledger
+        .transactions
+        .iter()
+        .filter(|transaction| transaction.date > today)
+        .map(|transaction| QualityTransaction {
+            date: transaction.date,
+            payee: transaction.description.clone(),
+            postings: get_postings_text(transaction).join(", "),
+            count: 1,
+        })
+        .collect::<Vec<QualityTransaction>>();
+
+    DataQualityReport {
+        issues: catch_all_postings.len()
+            + missing_prices.len()
+            + stale_prices.len()
+            + duplicates.len()
+            + future_transactions.len(),
+        catch_all_postings,
+        missing_prices,
+        stale_prices,
+        duplicates,
+        future_transactions,
+    }
+}
+
+fn get_catch_all_postings(
+    ledger: &simplified_ledger::Ledger,
+    catch_all_accounts: &[String],
+) -> Vec<QualityPosting> {
+    let mut postings = Vec::new();
+    for transaction in &ledger.transactions {
+        for posting in &transaction.postings {
+            if catch_all_accounts.iter().any(|account| {
+                posting.account == *account || posting.account.starts_with(&format!("{}:", account))
+            }) {
+                postings.push(QualityPosting {
+                    date: transaction.date,
+                    payee: transaction.description.clone(),
+                    account: posting.account.clone(),
+                    amount: posting.amount.to_string(),
+                });
+            }
+        }
+    }
+    postings
+}
+
+/// Commodities of the current asset and liability balances without prices or with old prices.
+fn get_price_issues(
+    ledger: &simplified_ledger::Ledger,
+    prices: &Prices,
+    date: NaiveDate,
+    stale_price_days: i64,
+    params: &ReportParameters,
+) -> (Vec<MissingPrice>, Vec<StalePrice>) {
+    let mut balances: HashMap<(&str, &str), Amount> = HashMap::new();
+    for transaction in &ledger.transactions {
+        for posting in &transaction.postings {
+            let commodity = posting.amount.commodity.name.as_str();
+            let is_held = params
+                .assets
+                .iter()
+                .chain(&params.liabilities)
+                .any(|prefix| posting.account.starts_with(prefix.as_str()));
+            if commodity == params.main_commodity || !is_held {
+                continue;
+            }
+            balances
+                .entry((&posting.account, commodity))
+                .and_modify(|amount| amount.quantity += posting.amount.quantity)
+                .or_insert_with(|| posting.amount.clone());
+        }
+    }
+
+    let mut held: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for ((account, commodity), amount) in &balances {
+        if !amount.quantity.is_zero() {
+            held.entry(commodity).or_default().insert(account);
+        }
+    }
+
+    let mut missing_prices = Vec::new();
+    let mut stale_prices = Vec::new();
+    for (commodity, accounts) in held {
+        let last_price_date =
+            get_price(commodity, date, prices, params).map(|(price_date, _)| price_date);
+        match last_price_date {
+            None => missing_prices.push(MissingPrice {
+                commodity: commodity.to_string(),
+                accounts: accounts.into_iter().map(str::to_string).collect(),
+            }),
+            Some(last_price_date) => {
+                let days = (date - last_price_date).num_days();
+                if days > stale_price_days {
+                    stale_prices.push(StalePrice {
+                        commodity: commodity.to_string(),
+                        last_price_date,
+                        days,
+                    });
+                }
+            }
+        }
+    }
+    (missing_prices, stale_prices)
+}
+
+/// Transactions with the same date, payee and asset and liability postings
+/// (e.g. the same bank operation imported twice and categorized differently).
+/// Transactions without such postings are compared by all the postings.
+fn get_duplicates(
+    ledger: &simplified_ledger::Ledger,
+    params: &ReportParameters,
+) -> Vec<QualityTransaction> {
+    // payees differing only in letter case or spaces are the same
+    let mut duplicates: BTreeMap<(NaiveDate, String, Vec<String>), QualityTransaction> =
+        BTreeMap::new();
+    for transaction in &ledger.transactions {
+        let mut postings: Vec<String> = transaction
+            .postings
+            .iter()
+            .filter(|posting| {
+                params
+                    .assets
+                    .iter()
+                    .chain(&params.liabilities)
+                    .any(|prefix| posting.account.starts_with(prefix.as_str()))
+            })
+            .map(|posting| format!("{} {}", posting.account, posting.amount))
+            .collect();
+        if postings.is_empty() {
+            postings = get_postings_text(transaction);
+        }
+        postings.sort();
+        let payee = transaction
+            .description
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .to_lowercase();
+        duplicates
+            .entry((transaction.date, payee, postings.clone()))
+            .or_insert_with(|| QualityTransaction {
+                date: transaction.date,
+                payee: transaction.description.clone(),
+                postings: postings.join(", "),
+                count: 0,
+            })
+            .count += 1;
+    }
+
+    duplicates
+        .into_values()
+        .filter(|duplicate| duplicate.count > 1)
+        .collect()
+}
+
+fn get_postings_text(transaction: &simplified_ledger::Transaction) -> Vec<String> {
+    transaction
+        .postings
+        .iter()
+        .map(|posting| format!("{} {}", posting.account, posting.amount))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn get_params() -> ReportParameters {
+        toml::from_str(
+            r#"
+main_commodity = "PLN"
+main_commodity_decimal_points = 2
+assets = [ "Assets" ]
+income = [ "Income" ]
+expenses = [ "Expenses" ]
+liabilities = [ "Liabilities" ]
+assets_liquid = [ "Assets:Bank" ]
+assets_fixed = []
+assets_high_risk = []
+job_income = []
+investment_income = []
+"#,
+        )
+        .unwrap()
+    }
+
+    fn get_ledger(ledger: &str) -> simplified_ledger::Ledger {
+        simplified_ledger::Ledger::try_from(ledger_parser::parse(ledger).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn duplicates_categorized_differently() {
+        let ledger = get_ledger(
+            r#"
+2024-01-05 SHOP  123
+    Expenses:Food  50 PLN
+    Assets:Bank  -50 PLN
+
+2024-01-05 Shop 123
+    Expenses:Unknown  50 PLN
+    Assets:Bank  -50 PLN
+
+2024-01-05 Shop 123
+    Expenses:Food  20 PLN
+    Assets:Bank  -20 PLN
+"#,
+        );
+        let duplicates = get_duplicates(&ledger, &get_params());
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].count, 2);
+        assert_eq!(duplicates[0].postings, "Assets:Bank -50 PLN");
+    }
+
+    #[test]
+    fn duplicates_without_asset_postings() {
+        let ledger = get_ledger(
+            r#"
+2024-01-05 Gift
+    Expenses:Gifts  50 PLN
+    Income:Other  -50 PLN
+
+2024-01-05 Gift
+    Expenses:Gifts  50 PLN
+    Income:Other  -50 PLN
+
+2024-01-06 Gift
+    Expenses:Gifts  50 PLN
+    Income:Other  -50 PLN
+"#,
+        );
+        let duplicates = get_duplicates(&ledger, &get_params());
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].date, NaiveDate::from_ymd(2024, 1, 5));
+    }
+}
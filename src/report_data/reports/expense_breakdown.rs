@@ -0,0 +1,343 @@
+use crate::configuration::ReportParameters;
+use crate::date_utils::last_day_in_month;
+use crate::price_index::PriceIndex;
+use crate::report_data::monthly_table::get_balance_value;
+use crate::report_data::reports::MonthlyAnomaly;
+use crate::report_data::structures::{BarChart, BarChartSerie, Table, TableCell, TableRow};
+use chrono::{Datelike, NaiveDate};
+use handlebars::to_json;
+use ledger_utils::monthly_report::MonthlyReport;
+use ledger_utils::prices::Prices;
+use rust_decimal::prelude::{ToPrimitive, Zero};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Monthly expenses split into categories.
+/// Categories with sub-accounts have their own breakdowns (drill-down).
+#[derive(Serialize)]
+pub struct ExpenseBreakdown {
+    pub id: String,
+    pub title: String,
+    pub parent_id: Option<String>,
+    pub categories: Vec<ExpenseCategory>,
+    pub chart: BarChart,
+    pub table: Table,
+}
+
+#[derive(Serialize)]
+pub struct ExpenseCategory {
+    pub name: String,
+    pub account: String,
+    pub breakdown_id: Option<String>,
+}
+
+/// Expenses of every account in a particular month (in the main commodity).
+pub struct MonthlyExpenses {
+    pub date: NaiveDate,
+    pub accounts: HashMap<String, Decimal>,
+}
+
+pub fn get_monthly_expenses(
+    monthly_report: &MonthlyReport,
+    prices: &Prices,
+    params: &ReportParameters,
+) -> Vec<MonthlyExpenses> {
+    monthly_report
+        .monthly_balances
+        .iter()
+        .map(|monthly_balance| {
+            let last_day = last_day_in_month(monthly_balance.year, monthly_balance.month);
+            let accounts = monthly_balance
+                .monthly_change
+                .account_balances
+                .iter()
+                .filter(|(account, _)| params.expenses.iter().any(|p| account.starts_with(p)))
+                .map(|(account, balance)| {
+                    let value = get_balance_value(balance, last_day, prices, params);
+                    (account.clone(), value)
+                })
+                .collect();
+
+            MonthlyExpenses {
+                date: last_day,
+                accounts,
+            }
+        })
+        .collect()
+}
+
+/// Converts the expenses into prices of the base date (see `MonthlyTable::convert_to_real_values`).
+pub fn convert_expenses_to_real_values(
+    monthly_expenses: &mut [MonthlyExpenses],
+    price_index: &PriceIndex,
+    base_date: NaiveDate,
+    params: &ReportParameters,
+) {
+    for month in monthly_expenses {
+        let factor = price_index.get_real_value_factor(month.date, base_date);
+        for value in month.accounts.values_mut() {
+            *value = (*value * factor).round_dp_with_strategy(
+                params.main_commodity_decimal_points,
+                RoundingStrategy::MidpointAwayFromZero,
+            );
+        }
+    }
+}
+
+/// Unusual months of the categories are highlighted in the top level breakdown.
+pub fn get_expense_breakdowns(
+    monthly_expenses: &[MonthlyExpenses],
+    anomalies: &[MonthlyAnomaly],
+    params: &ReportParameters,
+) -> Vec<ExpenseBreakdown> {
+    let mut breakdowns = Vec::new();
+    if monthly_expenses.is_empty() {
+        return breakdowns;
+    }
+
+    let get_category = |account: &str| get_expense_category(account, params);
+
+    // with a single expenses account categories are named relative to it
+    let title = if params.expenses.len() == 1 {
+        params.expenses[0].clone()
+    } else {
+        "Expenses".to_string()
+    };
+
+    add_breakdown(
+        &mut breakdowns,
+        monthly_expenses,
+        title,
+        None,
+        &get_category,
+        anomalies,
+    );
+
+    breakdowns
+}
+
+/// Adds the breakdown and (recursively) breakdowns of its categories.
+fn add_breakdown(
+    breakdowns: &mut Vec<ExpenseBreakdown>,
+    monthly_expenses: &[MonthlyExpenses],
+    title: String,
+    parent_id: Option<String>,
+    get_category: &dyn Fn(&str) -> Option<String>,
+    anomalies: &[MonthlyAnomaly],
+) {
+    let id = format!("expenseBreakdown{}", breakdowns.len());
+
+    // group accounts into categories
+    let mut totals: HashMap<String, Decimal> = HashMap::new();
+    let mut with_sub_accounts: Vec<String> = Vec::new();
+    for month in monthly_expenses {
+        for (account, value) in &month.accounts {
+            if let Some(category) = get_category(account) {
+                *totals.entry(category.clone()).or_insert_with(Decimal::zero) += *value;
+                if account.starts_with(&format!("{}:", category))
+                    && !with_sub_accounts.contains(&category)
+                {
+                    with_sub_accounts.push(category);
+                }
+            }
+        }
+    }
+
+    // the biggest categories first
+    let mut category_accounts: Vec<String> = totals.keys().cloned().collect();
+    category_accounts.sort_by(|a1, a2| totals[a2].cmp(&totals[a1]).then(a1.cmp(a2)));
+    let category_names: Vec<String> = category_accounts
+        .iter()
+        .map(|account| get_category_name(&title, account))
+        .collect();
+
+    let values: Vec<Vec<Decimal>> = monthly_expenses
+        .iter()
+        .map(|month| {
+            let mut values = vec![Decimal::zero(); category_accounts.len()];
+            for (account, value) in &month.accounts {
+                if let Some(category) = get_category(account) {
+                    if let Some(pos) = category_accounts.iter().position(|c| *c == category) {
+                        values[pos] += *value;
+                    }
+                }
+            }
+            values
+        })
+        .collect();
+
+    let pos = breakdowns.len();
+    breakdowns.push(ExpenseBreakdown {
+        id: id.clone(),
+        title: title.clone(),
+        parent_id,
+        categories: Vec::new(),
+        chart: get_breakdown_chart(&id, monthly_expenses, &category_names, &values),
+        table: get_breakdown_table(
+            monthly_expenses,
+            &category_accounts,
+            &category_names,
+            &values,
+            anomalies,
+        ),
+    });
+
+    let mut categories = Vec::new();
+    for (category_account, name) in category_accounts.into_iter().zip(category_names) {
+        let breakdown_id = if with_sub_accounts.contains(&category_account) {
+            let child_id = format!("expenseBreakdown{}", breakdowns.len());
+            let get_sub_category = |account: &str| {
+                if account == category_account
+                    || account.starts_with(&format!("{}:", category_account))
+                {
+                    Some(get_sub_account(&category_account, account, 1))
+                } else {
+                    None
+                }
+            };
+            add_breakdown(
+                breakdowns,
+                monthly_expenses,
+                category_account.clone(),
+                Some(id.clone()),
+                &get_sub_category,
+                &[],
+            );
+            Some(child_id)
+        } else {
+            None
+        };
+
+        categories.push(ExpenseCategory {
+            name,
+            account: category_account,
+            breakdown_id,
+        });
+    }
+    breakdowns[pos].categories = categories;
+}
+
+/// Top level category of the expense account (see `expenses_categories_depth`).
+pub fn get_expense_category(account: &str, params: &ReportParameters) -> Option<String> {
+    params
+        .expenses
+        .iter()
+        .find(|prefix| account.starts_with(prefix.as_str()))
+        .map(|prefix| get_sub_account(prefix, account, params.expenses_categories_depth.max(1)))
+}
+
+/// Returns parent account extended with (at most) `depth` parts of the account name,
+/// e.g. for `Expenses`, `Expenses:Food:Restaurants` and depth 1 returns `Expenses:Food`.
+fn get_sub_account(parent: &str, account: &str, depth: usize) -> String {
+    let rest = account[parent.len()..].trim_start_matches(':');
+    if rest.is_empty() {
+        return parent.to_string();
+    }
+    let sub_account: Vec<&str> = rest.split(':').take(depth).collect();
+    format!("{}:{}", parent.trim_end_matches(':'), sub_account.join(":"))
+}
+
+fn get_category_name(parent: &str, category_account: &str) -> String {
+    match category_account.strip_prefix(&format!("{}:", parent)) {
+        Some(name) => name.to_string(),
+        None => category_account.to_string(),
+    }
+}
+
+fn get_breakdown_chart(
+    id: &str,
+    monthly_expenses: &[MonthlyExpenses],
+    category_names: &[String],
+    values: &[Vec<Decimal>],
+) -> BarChart {
+    let series: Vec<BarChartSerie> = category_names
+        .iter()
+        .enumerate()
+        .map(|(category_pos, category_name)| BarChartSerie {
+            key: category_name.clone(),
+            serie_type: "bar".to_string(),
+            values: monthly_expenses
+                .iter()
+                .zip(values)
+                .map(|(month, values)| {
+                    [
+                        month.date.and_hms(0, 0, 0).timestamp_millis() as f64,
+                        values[category_pos].to_f64().unwrap(),
+                    ]
+                })
+                .collect(),
+        })
+        .collect();
+
+    BarChart {
+        id: format!("{}Chart", id),
+        digit_points: 0,
+        series: to_json(series).to_string(),
+    }
+}
+
+fn get_breakdown_table(
+    monthly_expenses: &[MonthlyExpenses],
+    category_accounts: &[String],
+    category_names: &[String],
+    values: &[Vec<Decimal>],
+    anomalies: &[MonthlyAnomaly],
+) -> Table {
+    let mut headers = vec!["Date".to_string()];
+    headers.extend(category_names.iter().cloned());
+    headers.push("Total".to_string());
+
+    let mut rows: Vec<TableRow> = Vec::new();
+    let mut totals = vec![Decimal::zero(); category_names.len() + 1];
+
+    for (month, values) in monthly_expenses.iter().zip(values) {
+        let mut columns = vec![TableCell::Month {
+            year: month.date.year(),
+            month: month.date.month(),
+        }];
+        let mut month_total = Decimal::zero();
+        for (pos, value) in values.iter().enumerate() {
+            let anomaly = anomalies
+                .iter()
+                .find(|a| a.date == month.date && a.category == category_accounts[pos]);
+            let cell = match anomaly {
+                Some(anomaly) => TableCell::Highlighted {
+                    value: *value,
+                    note: format!(
+                        "Unusual month (trailing mean {}, standard deviation {})",
+                        anomaly.trailing_mean, anomaly.std_deviation
+                    ),
+                },
+                None => TableCell::Value(*value),
+            };
+            columns.push(TableCell::Register {
+                cell: Box::new(cell),
+                account: category_accounts[pos].clone(),
+                month: Some(month.date.format("%Y/%m").to_string()),
+            });
+            totals[pos] += *value;
+            month_total += *value;
+        }
+        columns.push(TableCell::Value(month_total));
+        totals[category_names.len()] += month_total;
+
+        rows.push(TableRow { columns });
+    }
+
+    let months = Decimal::from(monthly_expenses.len());
+    let mut total_columns = vec![TableCell::Text("Total".to_string())];
+    let mut average_columns = vec![TableCell::Text("Average".to_string())];
+    for total in totals {
+        total_columns.push(TableCell::Value(total));
+        average_columns.push(TableCell::Value((total / months).round_dp(2)));
+    }
+    rows.push(TableRow {
+        columns: total_columns,
+    });
+    rows.push(TableRow {
+        columns: average_columns,
+    });
+
+    Table { headers, rows }
+}
@@ -0,0 +1,227 @@
+use crate::configuration::ReportParameters;
+use crate::date_utils::last_day_in_month;
+use crate::report_data::monthly_table::get_amount_value;
+use crate::report_data::structures::{AreaChart, AreaChartSerie, Table, TableCell, TableRow};
+use chrono::{Datelike, NaiveDate};
+use handlebars::to_json;
+use ledger_utils::account_balance::AccountBalance;
+use ledger_utils::monthly_report::MonthlyReport;
+use ledger_utils::prices::Prices;
+use ledger_utils::Amount;
+use rust_decimal::prelude::{ToPrimitive, Zero};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Holdings (assets and liabilities) per commodity.
+#[derive(Serialize)]
+pub struct ExposureReport {
+    pub chart: AreaChart,
+    /// Holdings of the last month and their share of the net worth.
+    pub current_table: Table,
+    /// Monthly values and the asset change split into exchange rate effect and flows.
+    pub table: Table,
+}
+
+struct ExposureMonth {
+    date: NaiveDate,
+    amounts: HashMap<String, Amount>,
+    /// Values (in the main commodity) of the commodities.
+    values: HashMap<String, Decimal>,
+    /// Value change caused by the exchange rates of the commodities held in the previous month.
+    exchange_rate_effect: Decimal,
+    /// Value change caused by the changed quantities.
+    flows: Decimal,
+}
+
+pub fn get_exposure_report(
+    monthly_report: &MonthlyReport,
+    prices: &Prices,
+    params: &ReportParameters,
+) -> Option<ExposureReport> {
+    let months = get_exposure_months(monthly_report, prices, params);
+    let last_month = months.last()?;
+
+    // the biggest holdings first
+    let mut commodities: Vec<String> = months
+        .iter()
+        .flat_map(|month| month.values.keys().cloned())
+        .collect();
+    commodities.sort();
+    commodities.dedup();
+    let last_value = |commodity: &String| {
+        last_month
+            .values
+            .get(commodity)
+            .copied()
+            .unwrap_or_else(Decimal::zero)
+    };
+    commodities.sort_by(|c1, c2| last_value(c2).cmp(&last_value(c1)).then(c1.cmp(c2)));
+
+    Some(ExposureReport {
+        chart: get_exposure_chart(&months, &commodities),
+        current_table: get_current_table(last_month, &commodities),
+        table: get_exposure_table(&months, &commodities),
+    })
+}
+
+fn get_exposure_months(
+    monthly_report: &MonthlyReport,
+    prices: &Prices,
+    params: &ReportParameters,
+) -> Vec<ExposureMonth> {
+    let is_balance_account = |account: &str| {
+        params
+            .assets
+            .iter()
+            .chain(params.liabilities.iter())
+            .any(|prefix| account.starts_with(prefix.as_str()))
+    };
+
+    let mut months: Vec<ExposureMonth> = Vec::new();
+    for monthly_balance in &monthly_report.monthly_balances {
+        let last_day = last_day_in_month(monthly_balance.year, monthly_balance.month);
+
+        let mut holdings = AccountBalance::new();
+        for (account, balance) in &monthly_balance.total.account_balances {
+            if is_balance_account(account) {
+                holdings += balance;
+            }
+        }
+        let amounts: HashMap<String, Amount> = holdings
+            .amounts
+            .into_iter()
+            .filter(|(_, amount)| !amount.quantity.is_zero())
+            .collect();
+        let values: HashMap<String, Decimal> = amounts
+            .iter()
+            .map(|(commodity, amount)| {
+                (
+                    commodity.clone(),
+                    get_amount_value(amount, last_day, prices, params),
+                )
+            })
+            .collect();
+
+        // previous quantities valued with the current and the previous exchange rates
+        let mut exchange_rate_effect = Decimal::zero();
+        let mut previous_total = Decimal::zero();
+        if let Some(previous) = months.last() {
+            for (commodity, amount) in &previous.amounts {
+                let previous_value = previous.values[commodity];
+                exchange_rate_effect +=
+                    get_amount_value(amount, last_day, prices, params) - previous_value;
+                previous_total += previous_value;
+            }
+        }
+        let total: Decimal = values.values().sum();
+
+        months.push(ExposureMonth {
+            date: last_day,
+            flows: total - previous_total - exchange_rate_effect,
+            amounts,
+            values,
+            exchange_rate_effect,
+        });
+    }
+
+    months
+}
+
+fn get_value(values: &HashMap<String, Decimal>, commodity: &str) -> Decimal {
+    values.get(commodity).copied().unwrap_or_else(Decimal::zero)
+}
+
+fn get_current_table(month: &ExposureMonth, commodities: &[String]) -> Table {
+    let headers = vec![
+        "Commodity".to_string(),
+        "Quantity".to_string(),
+        "Value".to_string(),
+        "Share of Net Worth".to_string(),
+    ];
+
+    let net_worth: Decimal = month.values.values().sum();
+    let rows = commodities
+        .iter()
+        .filter_map(|commodity| {
+            let amount = month.amounts.get(commodity)?;
+            let value = get_value(&month.values, commodity);
+            let share = if net_worth.is_zero() {
+                Decimal::zero()
+            } else {
+                value * Decimal::new(100, 0) / net_worth
+            };
+            Some(TableRow {
+                columns: vec![
+                    TableCell::Text(commodity.clone()),
+                    TableCell::Value(amount.quantity),
+                    TableCell::Value(value),
+                    TableCell::Percent(share),
+                ],
+            })
+        })
+        .collect();
+
+    Table { headers, rows }
+}
+
+fn get_exposure_table(months: &[ExposureMonth], commodities: &[String]) -> Table {
+    let mut headers = vec!["Date".to_string()];
+    headers.extend(commodities.iter().cloned());
+    headers.push("Exchange Rate Effect".to_string());
+    headers.push("Flows".to_string());
+    headers.push("Total Change".to_string());
+
+    let rows = months
+        .iter()
+        .map(|month| {
+            let mut columns = vec![TableCell::Month {
+                year: month.date.year(),
+                month: month.date.month(),
+            }];
+            for commodity in commodities {
+                columns.push(TableCell::Value(get_value(&month.values, commodity)));
+            }
+            columns.push(TableCell::Value(month.exchange_rate_effect));
+            columns.push(TableCell::Value(month.flows));
+            columns.push(TableCell::Value(month.exchange_rate_effect + month.flows));
+            TableRow { columns }
+        })
+        .collect();
+
+    Table { headers, rows }
+}
+
+fn get_exposure_chart(months: &[ExposureMonth], commodities: &[String]) -> AreaChart {
+    let min_date = months[0].date.and_hms(0, 0, 0).timestamp_millis();
+    let max_date = months
+        .last()
+        .unwrap()
+        .date
+        .and_hms(0, 0, 0)
+        .timestamp_millis();
+
+    let series: Vec<AreaChartSerie> = commodities
+        .iter()
+        .map(|commodity| AreaChartSerie {
+            key: commodity.clone(),
+            values: months
+                .iter()
+                .map(|month| {
+                    [
+                        month.date.and_hms(0, 0, 0).timestamp_millis() as f64,
+                        get_value(&month.values, commodity).to_f64().unwrap(),
+                    ]
+                })
+                .collect(),
+        })
+        .collect();
+
+    AreaChart {
+        id: "exposureChart".to_string(),
+        min_x: min_date as f64,
+        max_x: max_date as f64,
+        digit_points: 0,
+        series: to_json(series).to_string(),
+    }
+}
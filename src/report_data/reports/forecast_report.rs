@@ -0,0 +1,269 @@
+use crate::configuration::{Forecast, Month, ReportParameters, ScheduledAmount};
+use crate::date_utils::last_day_in_month;
+use crate::report_data::monthly_table::MonthlyTable;
+use crate::report_data::reports::expense_breakdown::{get_expense_category, MonthlyExpenses};
+use crate::report_data::reports::recurring_report::{RecurringReport, RecurringRow};
+use crate::report_data::structures::{LineChart, LineChartSerie};
+use chrono::{Datelike, NaiveDate};
+use handlebars::to_json;
+use rust_decimal::prelude::{ToPrimitive, Zero};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Projection of the assets based on the average income, the average expenses
+/// of the categories, the active recurring payments and the scheduled amounts.
+#[derive(Serialize)]
+pub struct ForecastReport {
+    pub main_commodity: String,
+    pub average_months: usize,
+    pub average_income: Decimal,
+    pub average_expenses: Decimal,
+    /// Averages without the recurring payments, the biggest categories first.
+    pub average_categories: Vec<ForecastCategory>,
+    /// Active recurring payments projected on their cadence.
+    pub recurring_payments: usize,
+    pub emergency_fund: Option<Decimal>,
+    /// The first projected month with liquid assets below the emergency fund.
+    pub below_emergency_fund: Option<String>,
+    pub chart: LineChart,
+    pub rows: Vec<ForecastRow>,
+}
+
+#[derive(Serialize)]
+pub struct ForecastCategory {
+    pub name: String,
+    pub average: Decimal,
+}
+
+#[derive(Serialize)]
+pub struct ForecastRow {
+    pub period: String,
+    #[serde(skip)]
+    pub date: NaiveDate,
+    pub income: Decimal,
+    /// Category averages (without the recurring payments).
+    pub expenses: Decimal,
+    pub recurring: Decimal,
+    pub scheduled: Decimal,
+    pub scheduled_names: Vec<String>,
+    pub liquid_assets: Decimal,
+    pub assets_total_net: Decimal,
+    pub is_below_emergency_fund: bool,
+}
+
+pub fn get_forecast_report(
+    monthly_table: &MonthlyTable,
+    monthly_expenses: &[MonthlyExpenses],
+    recurring_report: &RecurringReport,
+    forecast: &Forecast,
+    params: &ReportParameters,
+) -> Option<ForecastReport> {
+    let last_row = monthly_table.rows.last()?;
+
+    // averages of the last months
+    let average_months = forecast.average_months.clamp(1, monthly_table.rows.len());
+    let first = monthly_table.rows.len() - average_months;
+    let months = Decimal::from(average_months);
+    // income accounts have negative balances
+    let income = monthly_table.get_monthly_changes(|row| -row.income);
+    let expenses = monthly_table.get_monthly_changes(|row| row.expenses);
+    let average_income = (income[first..].iter().sum::<Decimal>() / months).round_dp(2);
+    let average_expenses = (expenses[first..].iter().sum::<Decimal>() / months).round_dp(2);
+
+    let mut category_totals: HashMap<String, Decimal> = HashMap::new();
+    for month in &monthly_expenses[monthly_expenses.len().saturating_sub(average_months)..] {
+        for (account, value) in &month.accounts {
+            if let Some(category) = get_expense_category(account, params) {
+                *category_totals
+                    .entry(category)
+                    .or_insert_with(Decimal::zero) += *value;
+            }
+        }
+    }
+
+    // recurring payments are projected separately,
+    // so their monthly cost is not a part of the category averages
+    let recurring_rows: Vec<&RecurringRow> = recurring_report
+        .rows
+        .iter()
+        .filter(|row| row.is_active)
+        .collect();
+    for row in &recurring_rows {
+        if let Some(category) = get_expense_category(&row.account, params) {
+            if let Some(total) = category_totals.get_mut(&category) {
+                *total -= row.annual_cost / Decimal::new(12, 0) * months;
+            }
+        }
+    }
+
+    let mut average_categories: Vec<ForecastCategory> = category_totals
+        .into_iter()
+        .map(|(name, total)| ForecastCategory {
+            name,
+            average: (total / months).round_dp(2).max(Decimal::zero()),
+        })
+        .collect();
+    average_categories.sort_by(|c1, c2| c2.average.cmp(&c1.average).then(c1.name.cmp(&c2.name)));
+    let categories_expenses: Decimal = average_categories.iter().map(|c| c.average).sum();
+
+    let mut rows: Vec<ForecastRow> = Vec::new();
+    let mut liquid_assets = last_row.liquid_assets;
+    let mut assets_total_net = last_row.assets_total_net;
+    let mut month = Month {
+        year: last_row.date.year(),
+        month: last_row.date.month(),
+    };
+    for _ in 0..forecast.months {
+        month = get_next_month(month);
+        let scheduled_amounts: Vec<&ScheduledAmount> = forecast
+            .scheduled
+            .iter()
+            .filter(|scheduled| {
+                let months_from_start = get_months_between(scheduled.month, month);
+                months_from_start >= 0
+                    && scheduled.until.map(|until| month <= until).unwrap_or(true)
+                    && match scheduled.every_months {
+                        Some(every_months) if every_months > 0 => {
+                            months_from_start % every_months as i32 == 0
+                        }
+                        _ => months_from_start == 0,
+                    }
+            })
+            .collect();
+        let scheduled: Decimal = scheduled_amounts.iter().map(|s| s.amount).sum();
+        let recurring: Decimal = recurring_rows
+            .iter()
+            .map(|row| {
+                row.amount * Decimal::from(row.get_payments_in_month(month.year, month.month))
+            })
+            .sum();
+
+        let change = average_income - categories_expenses - recurring + scheduled;
+        liquid_assets += change;
+        assets_total_net += change;
+
+        rows.push(ForecastRow {
+            period: format!("{}/{:02}", month.year, month.month),
+            date: last_day_in_month(month.year, month.month),
+            income: average_income,
+            expenses: categories_expenses,
+            recurring,
+            scheduled,
+            scheduled_names: scheduled_amounts.iter().map(|s| s.name.clone()).collect(),
+            liquid_assets,
+            assets_total_net,
+            is_below_emergency_fund: forecast
+                .emergency_fund
+                .map(|emergency_fund| liquid_assets < emergency_fund)
+                .unwrap_or(false),
+        });
+    }
+
+    Some(ForecastReport {
+        main_commodity: params.main_commodity.clone(),
+        average_months,
+        average_income,
+        average_expenses,
+        average_categories,
+        recurring_payments: recurring_rows.len(),
+        emergency_fund: forecast.emergency_fund,
+        below_emergency_fund: rows
+            .iter()
+            .find(|row| row.is_below_emergency_fund)
+            .map(|row| row.period.clone()),
+        chart: get_forecast_chart(monthly_table, &rows, forecast.emergency_fund),
+        rows,
+    })
+}
+
+fn get_next_month(month: Month) -> Month {
+    if month.month == 12 {
+        Month {
+            year: month.year + 1,
+            month: 1,
+        }
+    } else {
+        Month {
+            year: month.year,
+            month: month.month + 1,
+        }
+    }
+}
+
+fn get_months_between(from: Month, to: Month) -> i32 {
+    (to.year - from.year) * 12 + to.month as i32 - from.month as i32
+}
+
+/// Projected values continue the actual ones (from the last actual month).
+fn get_forecast_chart(
+    monthly_table: &MonthlyTable,
+    rows: &[ForecastRow],
+    emergency_fund: Option<Decimal>,
+) -> LineChart {
+    let to_point = |date: NaiveDate, value: Decimal| {
+        [
+            date.and_hms(0, 0, 0).timestamp_millis() as f64,
+            value.to_f64().unwrap(),
+        ]
+    };
+    let last_row = monthly_table.rows.last().unwrap();
+    let min_date = monthly_table.rows[0].date;
+    let max_date = rows.last().map(|row| row.date).unwrap_or(last_row.date);
+
+    let mut series = vec![
+        LineChartSerie {
+            key: "Assets Total Net".to_string(),
+            area: true,
+            values: monthly_table
+                .rows
+                .iter()
+                .map(|row| to_point(row.date, row.assets_total_net))
+                .collect(),
+        },
+        LineChartSerie {
+            key: "Assets Total Net (Projected)".to_string(),
+            area: false,
+            values: std::iter::once(to_point(last_row.date, last_row.assets_total_net))
+                .chain(
+                    rows.iter()
+                        .map(|row| to_point(row.date, row.assets_total_net)),
+                )
+                .collect(),
+        },
+        LineChartSerie {
+            key: "Liquid Assets".to_string(),
+            area: true,
+            values: monthly_table
+                .rows
+                .iter()
+                .map(|row| to_point(row.date, row.liquid_assets))
+                .collect(),
+        },
+        LineChartSerie {
+            key: "Liquid Assets (Projected)".to_string(),
+            area: false,
+            values: std::iter::once(to_point(last_row.date, last_row.liquid_assets))
+                .chain(rows.iter().map(|row| to_point(row.date, row.liquid_assets)))
+                .collect(),
+        },
+    ];
+    if let Some(emergency_fund) = emergency_fund {
+        series.push(LineChartSerie {
+            key: "Emergency Fund".to_string(),
+            area: false,
+            values: vec![
+                to_point(min_date, emergency_fund),
+                to_point(max_date, emergency_fund),
+            ],
+        });
+    }
+
+    LineChart {
+        id: "forecastChart".to_string(),
+        min_x: min_date.and_hms(0, 0, 0).timestamp_millis() as f64,
+        max_x: max_date.and_hms(0, 0, 0).timestamp_millis() as f64,
+        digit_points: 0,
+        series: to_json(series).to_string(),
+    }
+}
@@ -0,0 +1,66 @@
+use crate::configuration::ReportParameters;
+use crate::report_data::monthly_table::MonthlyTable;
+use crate::report_data::structures::{BarChart, BarChartSerie};
+use crate::report_data::time_series::moving_average;
+use handlebars::to_json;
+use rust_decimal::prelude::ToPrimitive;
+
+pub fn get_income_chart(monthly_table: &MonthlyTable, params: &ReportParameters) -> BarChart {
+    // income accounts have negative balances
+    let income = monthly_table.get_monthly_changes(|row| -row.income);
+    let job_income = monthly_table.get_monthly_changes(|row| -row.job_income);
+    let investment_income = monthly_table.get_monthly_changes(|row| -row.investment_income);
+
+    let mut series_job_income = Vec::new();
+    let mut series_investment_income = Vec::new();
+    let mut series_other_income = Vec::new();
+    for (pos, row) in monthly_table.rows.iter().enumerate() {
+        let date = row.date.and_hms(0, 0, 0).timestamp_millis() as f64;
+        let other_income = income[pos] - job_income[pos] - investment_income[pos];
+        series_job_income.push([date, job_income[pos].to_f64().unwrap()]);
+        series_investment_income.push([date, investment_income[pos].to_f64().unwrap()]);
+        series_other_income.push([date, other_income.to_f64().unwrap()]);
+    }
+
+    let mut series = vec![
+        BarChartSerie {
+            key: "Job Income".to_string(),
+            serie_type: "bar".to_string(),
+            values: series_job_income,
+        },
+        BarChartSerie {
+            key: "Investment Income".to_string(),
+            serie_type: "bar".to_string(),
+            values: series_investment_income,
+        },
+        BarChartSerie {
+            key: "Other Income".to_string(),
+            serie_type: "bar".to_string(),
+            values: series_other_income,
+        },
+    ];
+
+    for average in &params.moving_averages.income {
+        series.push(BarChartSerie {
+            key: average.name(),
+            serie_type: "line".to_string(),
+            values: monthly_table
+                .rows
+                .iter()
+                .zip(moving_average(&income, average))
+                .map(|(row, value)| {
+                    [
+                        row.date.and_hms(0, 0, 0).timestamp_millis() as f64,
+                        value.to_f64().unwrap(),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    BarChart {
+        id: "incomeChart".to_string(),
+        digit_points: 0,
+        series: to_json(series).to_string(),
+    }
+}
@@ -0,0 +1,48 @@
+use crate::report_data::monthly_table::MonthlyTable;
+use crate::report_data::structures::{Table, TableCell, TableRow};
+use chrono::Datelike;
+use rust_decimal::prelude::Zero;
+use rust_decimal::Decimal;
+
+pub fn get_income_table(monthly_table: &MonthlyTable) -> Table {
+    let headers = vec![
+        "Date".to_string(),
+        "Total Income".to_string(),
+        "Job Income".to_string(),
+        "Investment Income".to_string(),
+        "Other Income".to_string(),
+        "Investment Share".to_string(),
+    ];
+
+    // income accounts have negative balances
+    let income = monthly_table.get_monthly_changes(|row| -row.income);
+    let job_income = monthly_table.get_monthly_changes(|row| -row.job_income);
+    let investment_income = monthly_table.get_monthly_changes(|row| -row.investment_income);
+
+    let mut rows: Vec<TableRow> = Vec::new();
+
+    for (pos, row) in monthly_table.rows.iter().enumerate() {
+        let other_income = income[pos] - job_income[pos] - investment_income[pos];
+        let investment_share = if income[pos].is_zero() {
+            Decimal::zero()
+        } else {
+            investment_income[pos] * Decimal::new(100, 0) / income[pos]
+        };
+
+        rows.push(TableRow {
+            columns: vec![
+                TableCell::Month {
+                    year: row.date.year(),
+                    month: row.date.month(),
+                },
+                TableCell::Value(income[pos]),
+                TableCell::Value(job_income[pos]),
+                TableCell::Value(investment_income[pos]),
+                TableCell::Value(other_income),
+                TableCell::Percent(investment_share),
+            ],
+        });
+    }
+
+    Table { headers, rows }
+}
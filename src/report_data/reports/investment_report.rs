@@ -0,0 +1,516 @@
+use crate::configuration::{InvestmentGroup, ReportParameters};
+use crate::date_utils::{last_day_in_month, ndays_in_month};
+use crate::posting_costs::PostingCosts;
+use crate::report_data::monthly_table::{get_amount_value, get_balance_value};
+use crate::report_data::structures::{LineChart, LineChartSerie, Table, TableCell, TableRow};
+use chrono::{Datelike, NaiveDate};
+use handlebars::to_json;
+use ledger_utils::account_balance::AccountBalance;
+use ledger_utils::monthly_report::MonthlyReport;
+use ledger_utils::prices::Prices;
+use ledger_utils::simplified_ledger;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive, Zero};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Performance of a group of investment accounts.
+#[derive(Serialize)]
+pub struct InvestmentReport {
+    pub name: String,
+    pub chart: LineChart,
+    /// Returns of every year and since inception.
+    pub periods_table: Table,
+    pub table: Table,
+}
+
+struct InvestmentMonth {
+    date: NaiveDate,
+    market_value: Decimal,
+    cost_basis: Decimal,
+    /// Value moved into (positive) or out of (negative) the investment accounts
+    /// from the other assets and liabilities.
+    contributions: Decimal,
+    cash_flows: Vec<(NaiveDate, Decimal)>,
+    /// Modified Dietz return of the month, in percents.
+    monthly_return: Decimal,
+}
+
+/// Quantity and cost of a commodity held in the investment accounts.
+#[derive(Default)]
+struct Holding {
+    quantity: Decimal,
+    cost: Decimal,
+}
+
+impl Holding {
+    /// Purchases add their cost, sales remove the average cost of the sold quantity.
+    fn add(&mut self, quantity: Decimal, cost: Decimal) {
+        if quantity.is_zero() {
+            return;
+        }
+        if self.quantity.is_zero()
+            || self.quantity.is_sign_positive() == quantity.is_sign_positive()
+        {
+            self.quantity += quantity;
+            self.cost += cost;
+        } else if quantity.abs() <= self.quantity.abs() {
+            self.cost -= self.cost * quantity.abs() / self.quantity.abs();
+            self.quantity += quantity;
+        } else {
+            // sold more than held, the rest opens a new position
+            let rest = quantity + self.quantity;
+            self.cost = cost * rest / quantity;
+            self.quantity = rest;
+        }
+    }
+}
+
+pub fn get_investment_reports(
+    ledger: &simplified_ledger::Ledger,
+    monthly_report: &MonthlyReport,
+    prices: &Prices,
+    posting_costs: &PostingCosts,
+    investments: &[InvestmentGroup],
+    params: &ReportParameters,
+) -> Vec<InvestmentReport> {
+    investments
+        .iter()
+        .enumerate()
+        .filter_map(|(pos, group)| {
+            let months =
+                get_investment_months(ledger, monthly_report, prices, posting_costs, group, params);
+            if months.is_empty() {
+                return None;
+            }
+            Some(InvestmentReport {
+                name: group.name.clone(),
+                chart: get_investment_chart(&format!("investmentChart{}", pos), &months),
+                periods_table: get_periods_table(&months),
+                table: get_investment_table(&months),
+            })
+        })
+        .collect()
+}
+
+fn get_investment_months(
+    ledger: &simplified_ledger::Ledger,
+    monthly_report: &MonthlyReport,
+    prices: &Prices,
+    posting_costs: &PostingCosts,
+    group: &InvestmentGroup,
+    params: &ReportParameters,
+) -> Vec<InvestmentMonth> {
+    let is_investment_account = |account: &str| {
+        group
+            .accounts
+            .iter()
+            .any(|prefix| account.starts_with(prefix.as_str()))
+    };
+    let is_other_balance_account = |account: &str| {
+        !is_investment_account(account)
+            && params
+                .assets
+                .iter()
+                .chain(params.liabilities.iter())
+                .any(|prefix| account.starts_with(prefix.as_str()))
+    };
+    // value of the posting at its cost (if given) or at the market price
+    let get_posting_value = |date: NaiveDate, posting: &simplified_ledger::Posting| {
+        let amount = posting_costs
+            .get_cost(date, posting)
+            .unwrap_or(&posting.amount);
+        get_amount_value(amount, date, prices, params)
+    };
+
+    let mut holdings: HashMap<String, Holding> = HashMap::new();
+    let mut transactions = ledger.transactions.iter().peekable();
+    let mut months: Vec<InvestmentMonth> = Vec::new();
+
+    for monthly_balance in &monthly_report.monthly_balances {
+        let last_day = last_day_in_month(monthly_balance.year, monthly_balance.month);
+
+        let mut cash_flows = Vec::new();
+        while let Some(transaction) = transactions.next_if(|t| t.date <= last_day) {
+            if !transaction
+                .postings
+                .iter()
+                .any(|p| is_investment_account(&p.account))
+            {
+                continue;
+            }
+
+            // postings of the same commodity (e.g. transfers between
+            // the investment accounts) are summed up first
+            let mut changes: HashMap<&str, (Decimal, Decimal)> = HashMap::new();
+            let mut cash_flow = Decimal::zero();
+            for posting in &transaction.postings {
+                if is_investment_account(&posting.account) {
+                    let change = changes
+                        .entry(&posting.amount.commodity.name)
+                        .or_insert_with(|| (Decimal::zero(), Decimal::zero()));
+                    change.0 += posting.amount.quantity;
+                    change.1 += get_posting_value(transaction.date, posting);
+                } else if is_other_balance_account(&posting.account) {
+                    cash_flow -= get_posting_value(transaction.date, posting);
+                }
+            }
+            for (commodity, (quantity, cost)) in changes {
+                holdings
+                    .entry(commodity.to_string())
+                    .or_default()
+                    .add(quantity, cost);
+            }
+            if !cash_flow.is_zero() {
+                cash_flows.push((transaction.date, cash_flow));
+            }
+        }
+
+        let mut investment_balance = AccountBalance::new();
+        for (account, balance) in &monthly_balance.total.account_balances {
+            if is_investment_account(account) {
+                investment_balance += balance;
+            }
+        }
+        let market_value = get_balance_value(&investment_balance, last_day, prices, params);
+        let cost_basis = holdings
+            .values()
+            .map(|holding| holding.cost)
+            .sum::<Decimal>()
+            .round_dp(params.main_commodity_decimal_points);
+
+        // the report starts with the first investment
+        if months.is_empty() && market_value.is_zero() && cash_flows.is_empty() {
+            continue;
+        }
+
+        let previous_value = months
+            .last()
+            .map(|m| m.market_value)
+            .unwrap_or_else(Decimal::zero);
+        months.push(InvestmentMonth {
+            date: last_day,
+            market_value,
+            cost_basis,
+            contributions: cash_flows.iter().map(|(_, value)| *value).sum(),
+            monthly_return: get_modified_dietz_return(previous_value, market_value, &cash_flows),
+            cash_flows,
+        });
+    }
+
+    months
+}
+
+/// Return of the month with the cash flows weighted by the time they were invested.
+fn get_modified_dietz_return(
+    start_value: Decimal,
+    end_value: Decimal,
+    cash_flows: &[(NaiveDate, Decimal)],
+) -> Decimal {
+    let mut total_cash_flow = Decimal::zero();
+    let mut weighted_cash_flow = Decimal::zero();
+    for (date, value) in cash_flows {
+        let days = ndays_in_month(date.year(), date.month());
+        total_cash_flow += *value;
+        weighted_cash_flow += *value * Decimal::from(days - date.day()) / Decimal::from(days);
+    }
+
+    let invested = start_value + weighted_cash_flow;
+    if invested <= Decimal::zero() {
+        Decimal::zero()
+    } else {
+        (end_value - start_value - total_cash_flow) * Decimal::new(100, 0) / invested
+    }
+}
+
+/// Time-weighted return (in percents) of the monthly returns.
+fn get_time_weighted_return(months: &[InvestmentMonth]) -> Decimal {
+    let hundred = Decimal::new(100, 0);
+    let growth = months.iter().fold(Decimal::new(1, 0), |growth, month| {
+        growth * (Decimal::new(1, 0) + month.monthly_return / hundred)
+    });
+    (growth - Decimal::new(1, 0)) * hundred
+}
+
+/// Money-weighted (internal) rate of return per year, in percents.
+/// The start value is treated as a contribution and the end value as a withdrawal.
+fn get_xirr(
+    start: (NaiveDate, Decimal),
+    months: &[InvestmentMonth],
+    end: (NaiveDate, Decimal),
+) -> Option<Decimal> {
+    let mut cash_flows = vec![(start.0, -start.1.to_f64()?)];
+    for month in months {
+        for (date, value) in &month.cash_flows {
+            cash_flows.push((*date, -value.to_f64()?));
+        }
+    }
+    cash_flows.push((end.0, end.1.to_f64()?));
+
+    let first_date = cash_flows.iter().map(|(date, _)| *date).min()?;
+    let net_present_value = |rate: f64| -> f64 {
+        cash_flows
+            .iter()
+            .map(|(date, value)| {
+                let years = (*date - first_date).num_days() as f64 / 365.0;
+                value / (1.0 + rate).powf(years)
+            })
+            .sum()
+    };
+
+    // bisection, the net present value decreases with the rate
+    let (mut low, mut high) = (-0.99, 10.0);
+    if !(net_present_value(low) > 0.0 && net_present_value(high) < 0.0) {
+        return None;
+    }
+    for _ in 0..100 {
+        let rate = (low + high) / 2.0;
+        if net_present_value(rate) > 0.0 {
+            low = rate;
+        } else {
+            high = rate;
+        }
+    }
+
+    Decimal::from_f64((low + high) / 2.0 * 100.0)
+}
+
+fn get_periods_table(months: &[InvestmentMonth]) -> Table {
+    let headers = vec![
+        "Period".to_string(),
+        "Start Value".to_string(),
+        "Contributions".to_string(),
+        "End Value".to_string(),
+        "Gain".to_string(),
+        "TWR".to_string(),
+        "XIRR (Yearly)".to_string(),
+    ];
+
+    // every year and since inception (as ranges of months)
+    let mut periods: Vec<(String, usize, usize)> = Vec::new();
+    let mut start = 0;
+    for pos in 0..months.len() {
+        let year = months[pos].date.year();
+        if months.get(pos + 1).map(|m| m.date.year()) != Some(year) {
+            periods.push((format!("{}", year), start, pos));
+            start = pos + 1;
+        }
+    }
+    periods.push(("Since Inception".to_string(), 0, months.len() - 1));
+
+    let rows = periods
+        .into_iter()
+        .map(|(name, start, end)| {
+            let period_months = &months[start..=end];
+            let start_value = if start > 0 {
+                (months[start - 1].date, months[start - 1].market_value)
+            } else {
+                let first_date = period_months[0]
+                    .cash_flows
+                    .first()
+                    .map(|(date, _)| *date)
+                    .unwrap_or(period_months[0].date);
+                (first_date, Decimal::zero())
+            };
+            let end_value = (months[end].date, months[end].market_value);
+            let contributions: Decimal = period_months.iter().map(|m| m.contributions).sum();
+            let xirr = get_xirr(start_value, period_months, end_value);
+
+            TableRow {
+                columns: vec![
+                    TableCell::Text(name),
+                    TableCell::Value(start_value.1),
+                    TableCell::Value(contributions),
+                    TableCell::Value(end_value.1),
+                    TableCell::Value(end_value.1 - start_value.1 - contributions),
+                    TableCell::Percent(get_time_weighted_return(period_months)),
+                    match xirr {
+                        Some(xirr) => TableCell::Percent(xirr),
+                        None => TableCell::Text("".to_string()),
+                    },
+                ],
+            }
+        })
+        .collect();
+
+    Table { headers, rows }
+}
+
+fn get_investment_table(months: &[InvestmentMonth]) -> Table {
+    let headers = vec![
+        "Date".to_string(),
+        "Market Value".to_string(),
+        "Cost Basis".to_string(),
+        "Unrealized Gain".to_string(),
+        "Contributions".to_string(),
+        "Return".to_string(),
+    ];
+
+    let rows = months
+        .iter()
+        .map(|month| TableRow {
+            columns: vec![
+                TableCell::Month {
+                    year: month.date.year(),
+                    month: month.date.month(),
+                },
+                TableCell::Value(month.market_value),
+                TableCell::Value(month.cost_basis),
+                TableCell::Value(month.market_value - month.cost_basis),
+                TableCell::Value(month.contributions),
+                TableCell::Percent(month.monthly_return),
+            ],
+        })
+        .collect();
+
+    Table { headers, rows }
+}
+
+fn get_investment_chart(id: &str, months: &[InvestmentMonth]) -> LineChart {
+    let min_date = months[0].date.and_hms(0, 0, 0).timestamp_millis();
+    let max_date = months
+        .last()
+        .unwrap()
+        .date
+        .and_hms(0, 0, 0)
+        .timestamp_millis();
+
+    let mut series_market_value = Vec::new();
+    let mut series_cost_basis = Vec::new();
+    for month in months {
+        let date = month.date.and_hms(0, 0, 0).timestamp_millis() as f64;
+        series_market_value.push([date, month.market_value.to_f64().unwrap()]);
+        series_cost_basis.push([date, month.cost_basis.to_f64().unwrap()]);
+    }
+
+    LineChart {
+        id: id.to_string(),
+        min_x: min_date as f64,
+        max_x: max_date as f64,
+        digit_points: 0,
+        series: to_json(vec![
+            LineChartSerie {
+                key: "Market Value".to_string(),
+                area: true,
+                values: series_market_value,
+            },
+            LineChartSerie {
+                key: "Cost Basis".to_string(),
+                area: false,
+                values: series_cost_basis,
+            },
+        ])
+        .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd(year, month, day)
+    }
+
+    fn month(
+        date: NaiveDate,
+        cash_flows: Vec<(NaiveDate, Decimal)>,
+        monthly_return: Decimal,
+    ) -> InvestmentMonth {
+        InvestmentMonth {
+            date,
+            market_value: Decimal::zero(),
+            cost_basis: Decimal::zero(),
+            contributions: cash_flows.iter().map(|(_, value)| *value).sum(),
+            cash_flows,
+            monthly_return,
+        }
+    }
+
+    #[test]
+    fn modified_dietz_return_without_cash_flows() {
+        let monthly_return =
+            get_modified_dietz_return(Decimal::from(1000), Decimal::from(1100), &[]);
+        assert_eq!(monthly_return, Decimal::from(10));
+    }
+
+    #[test]
+    fn modified_dietz_return_weights_cash_flows_by_days() {
+        // 1000 invested for 15 of the 31 days
+        let monthly_return = get_modified_dietz_return(
+            Decimal::from(1000),
+            Decimal::from(2100),
+            &[(date(2024, 1, 16), Decimal::from(1000))],
+        );
+        assert_eq!(monthly_return.round_dp(2), Decimal::new(674, 2));
+    }
+
+    #[test]
+    fn modified_dietz_return_without_invested_value() {
+        let monthly_return = get_modified_dietz_return(
+            Decimal::zero(),
+            Decimal::from(100),
+            &[(date(2024, 1, 31), Decimal::from(100))],
+        );
+        assert_eq!(monthly_return, Decimal::zero());
+    }
+
+    #[test]
+    fn time_weighted_return_compounds_monthly_returns() {
+        let months = [
+            month(date(2024, 1, 31), Vec::new(), Decimal::from(10)),
+            month(date(2024, 2, 29), Vec::new(), Decimal::from(-10)),
+        ];
+        assert_eq!(get_time_weighted_return(&months), Decimal::from(-1));
+        assert_eq!(get_time_weighted_return(&[]), Decimal::zero());
+    }
+
+    #[test]
+    fn xirr_of_one_year() {
+        let xirr = get_xirr(
+            (date(2023, 1, 1), Decimal::from(1000)),
+            &[],
+            (date(2024, 1, 1), Decimal::from(1100)),
+        )
+        .unwrap();
+        assert_eq!(xirr.round_dp(4), Decimal::from(10));
+    }
+
+    #[test]
+    fn xirr_includes_contributions() {
+        let months = [month(
+            date(2023, 1, 31),
+            vec![(date(2023, 1, 1), Decimal::from(1000))],
+            Decimal::zero(),
+        )];
+        let xirr = get_xirr(
+            (date(2023, 1, 1), Decimal::zero()),
+            &months,
+            (date(2024, 1, 1), Decimal::from(900)),
+        )
+        .unwrap();
+        assert_eq!(xirr.round_dp(4), Decimal::from(-10));
+    }
+
+    #[test]
+    fn xirr_without_sign_change_in_bracket() {
+        // more than 1000% per year
+        assert_eq!(
+            get_xirr(
+                (date(2023, 1, 1), Decimal::from(1000)),
+                &[],
+                (date(2024, 1, 1), Decimal::from(20000)),
+            ),
+            None
+        );
+        // everything lost
+        assert_eq!(
+            get_xirr(
+                (date(2023, 1, 1), Decimal::from(1000)),
+                &[],
+                (date(2024, 1, 1), Decimal::zero()),
+            ),
+            None
+        );
+    }
+}
@@ -1,11 +1,74 @@
+mod account_report;
+pub use account_report::*;
+
+mod allocation_report;
+pub use allocation_report::*;
+
+mod anomaly_report;
+pub use anomaly_report::*;
+
 mod assets_chart;
 pub use assets_chart::*;
 
 mod assets_table;
 pub use assets_table::*;
 
+mod attribution_report;
+pub use attribution_report::*;
+
+mod budget_report;
+pub use budget_report::*;
+
+mod data_quality_report;
+pub use data_quality_report::*;
+
+mod expense_breakdown;
+pub use expense_breakdown::*;
+
 mod expenses_chart;
 pub use expenses_chart::*;
 
+mod exposure_report;
+pub use exposure_report::*;
+
+mod forecast_report;
+pub use forecast_report::*;
+
+mod income_chart;
+pub use income_chart::*;
+
+mod income_table;
+pub use income_table::*;
+
+mod investment_report;
+pub use investment_report::*;
+
+mod net_worth_report;
+pub use net_worth_report::*;
+
+mod payee_report;
+pub use payee_report::*;
+
+mod reconcile_report;
+pub use reconcile_report::*;
+
+mod recurring_report;
+pub use recurring_report::*;
+
+mod register_report;
+pub use register_report::*;
+
+mod savings_report;
+pub use savings_report::*;
+
 mod summary_tree;
 pub use summary_tree::*;
+
+mod tag_report;
+pub use tag_report::*;
+
+mod year_over_year;
+pub use year_over_year::*;
+
+mod valuation_trace;
+pub use valuation_trace::*;
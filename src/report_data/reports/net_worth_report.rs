@@ -0,0 +1,143 @@
+use crate::configuration::ReportParameters;
+use crate::date_utils::last_day_in_month;
+use crate::price_index::PriceIndex;
+use crate::report_data::monthly_table::{MonthlyCalculator, MonthlyTable};
+use crate::report_data::structures::{LineChart, LineChartSerie, Table, TableCell, TableRow};
+use chrono::{Datelike, NaiveDate};
+use handlebars::to_json;
+use ledger_utils::monthly_report::MonthlyReport;
+use ledger_utils::prices::Prices;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// Net worth with liabilities broken down into accounts (one level below `liabilities` accounts).
+/// With real values the liability accounts are converted into prices of the base date,
+/// like the totals of the monthly table.
+pub fn get_net_worth_table(
+    monthly_report: &MonthlyReport,
+    monthly_table: &MonthlyTable,
+    prices: &Prices,
+    real_values: Option<(&PriceIndex, NaiveDate)>,
+    params: &ReportParameters,
+) -> Table {
+    let liability_accounts = get_liability_accounts(monthly_report, params);
+
+    let mut headers = vec![
+        "Date".to_string(),
+        "Net Worth".to_string(),
+        "Assets Total Net".to_string(),
+        "Liabilities".to_string(),
+    ];
+    headers.extend(liability_accounts.iter().cloned());
+
+    let mut rows: Vec<TableRow> = Vec::new();
+
+    for (monthly_balance, row) in monthly_report
+        .monthly_balances
+        .iter()
+        .zip(&monthly_table.rows)
+    {
+        let last_day = last_day_in_month(monthly_balance.year, monthly_balance.month);
+        let calc = MonthlyCalculator::new(&monthly_balance.total, prices, last_day, params);
+        let factor = match real_values {
+            Some((price_index, base_date)) => price_index.get_real_value_factor(last_day, base_date),
+            None => Decimal::new(1, 0),
+        };
+
+        let mut columns = vec![
+            TableCell::Month {
+                year: row.date.year(),
+                month: row.date.month(),
+            },
+            TableCell::Value(row.net_worth),
+            TableCell::Value(row.assets_total_net),
+            TableCell::Value(row.liabilities),
+        ];
+        for account in &liability_accounts {
+            // liability accounts have negative balances
+            let value = -calc.get_value(std::slice::from_ref(account)) * factor;
+            columns.push(TableCell::Value(value.round_dp_with_strategy(
+                params.main_commodity_decimal_points,
+                RoundingStrategy::MidpointAwayFromZero,
+            )));
+        }
+
+        rows.push(TableRow { columns });
+    }
+
+    Table { headers, rows }
+}
+
+pub fn get_net_worth_chart(monthly_table: &MonthlyTable) -> LineChart {
+    let min_date = monthly_table.rows[0]
+        .date
+        .and_hms(0, 0, 0)
+        .timestamp_millis();
+    let max_date = monthly_table
+        .rows
+        .last()
+        .unwrap()
+        .date
+        .and_hms(0, 0, 0)
+        .timestamp_millis();
+
+    let mut series_assets = Vec::new();
+    let mut series_liabilities = Vec::new();
+    let mut series_net_worth = Vec::new();
+    for row in &monthly_table.rows {
+        let date = row.date.and_hms(0, 0, 0).timestamp_millis() as f64;
+        series_assets.push([date, row.assets_total_net.to_f64().unwrap()]);
+        series_liabilities.push([date, row.liabilities.to_f64().unwrap()]);
+        series_net_worth.push([date, row.net_worth.to_f64().unwrap()]);
+    }
+
+    LineChart {
+        id: "netWorthChart".to_string(),
+        min_x: min_date as f64,
+        max_x: max_date as f64,
+        digit_points: 0,
+        series: to_json(vec![
+            LineChartSerie {
+                key: "Net Worth".to_string(),
+                area: true,
+                values: series_net_worth,
+            },
+            LineChartSerie {
+                key: "Assets Total Net".to_string(),
+                area: false,
+                values: series_assets,
+            },
+            LineChartSerie {
+                key: "Liabilities".to_string(),
+                area: false,
+                values: series_liabilities,
+            },
+        ])
+        .to_string(),
+    }
+}
+
+fn get_liability_accounts(
+    monthly_report: &MonthlyReport,
+    params: &ReportParameters,
+) -> Vec<String> {
+    let mut accounts: Vec<String> = Vec::new();
+    for monthly_balance in &monthly_report.monthly_balances {
+        for account in monthly_balance.total.account_balances.keys() {
+            for prefix in &params.liabilities {
+                if let Some(rest) = account.strip_prefix(prefix.as_str()) {
+                    let rest = rest.trim_start_matches(':');
+                    let liability_account = match rest.split(':').next() {
+                        Some(name) if !name.is_empty() => format!("{}:{}", prefix, name),
+                        _ => prefix.clone(),
+                    };
+                    if !accounts.contains(&liability_account) {
+                        accounts.push(liability_account);
+                    }
+                }
+            }
+        }
+    }
+    accounts.sort();
+    accounts
+}
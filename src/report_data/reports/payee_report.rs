@@ -0,0 +1,175 @@
+use crate::configuration::{Payees, ReportParameters};
+use crate::date_utils::last_day_in_month;
+use crate::report_data::monthly_table::get_amount_value;
+use crate::report_data::structures::{BarChart, BarChartSerie, Table, TableCell, TableRow};
+use chrono::{Datelike, NaiveDate};
+use handlebars::to_json;
+use ledger_utils::prices::Prices;
+use ledger_utils::simplified_ledger;
+use rust_decimal::prelude::{ToPrimitive, Zero};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Expenses by payee in the last months.
+#[derive(Serialize)]
+pub struct PayeeReport {
+    pub chart: BarChart,
+    /// Payees ranked by the total spend with their monthly history.
+    pub table: Table,
+}
+
+struct PayeeExpenses {
+    name: String,
+    total: Decimal,
+    transactions: usize,
+    /// Expenses in the months of the report.
+    monthly: Vec<Decimal>,
+}
+
+/// Number of the payees shown on the chart.
+const CHART_PAYEES: usize = 6;
+
+pub fn get_payee_report(
+    ledger: &simplified_ledger::Ledger,
+    prices: &Prices,
+    payees: &Payees,
+    params: &ReportParameters,
+) -> PayeeReport {
+    let months = get_report_months(ledger, payees.months);
+
+    // payees differing only in letter case are the same
+    let mut payee_expenses: HashMap<String, PayeeExpenses> = HashMap::new();
+    if let Some(first_month) = months.first() {
+        for transaction in &ledger.transactions {
+            if transaction.date < first_month.with_day(1).unwrap() {
+                continue;
+            }
+            let value: Decimal = transaction
+                .postings
+                .iter()
+                .filter(|posting| {
+                    params
+                        .expenses
+                        .iter()
+                        .any(|prefix| posting.account.starts_with(prefix.as_str()))
+                })
+                .map(|posting| get_amount_value(&posting.amount, transaction.date, prices, params))
+                .sum();
+            if value.is_zero() {
+                continue;
+            }
+
+            let name = payees.normalize(&transaction.description);
+            let expenses = payee_expenses
+                .entry(name.to_lowercase())
+                .or_insert_with(|| PayeeExpenses {
+                    name,
+                    total: Decimal::zero(),
+                    transactions: 0,
+                    monthly: vec![Decimal::zero(); months.len()],
+                });
+            expenses.total += value;
+            expenses.transactions += 1;
+            if let Some(pos) = months.iter().position(|month| {
+                month.year() == transaction.date.year() && month.month() == transaction.date.month()
+            }) {
+                expenses.monthly[pos] += value;
+            }
+        }
+    }
+
+    let mut payee_expenses: Vec<PayeeExpenses> = payee_expenses.into_values().collect();
+    payee_expenses.sort_by(|p1, p2| p2.total.cmp(&p1.total).then(p1.name.cmp(&p2.name)));
+    payee_expenses.truncate(payees.top);
+
+    PayeeReport {
+        chart: get_payee_chart(&months, &payee_expenses, params),
+        table: get_payee_table(&months, &payee_expenses, params),
+    }
+}
+
+/// Last days of the last months (up to the last transaction).
+fn get_report_months(ledger: &simplified_ledger::Ledger, count: usize) -> Vec<NaiveDate> {
+    let last_date = match ledger.transactions.iter().map(|t| t.date).max() {
+        Some(date) => date,
+        None => return Vec::new(),
+    };
+    let mut months = Vec::new();
+    let (mut year, mut month) = (last_date.year(), last_date.month());
+    for _ in 0..count.max(1) {
+        months.push(last_day_in_month(year, month));
+        if month == 1 {
+            year -= 1;
+            month = 12;
+        } else {
+            month -= 1;
+        }
+    }
+    months.reverse();
+    months
+}
+
+fn get_payee_chart(
+    months: &[NaiveDate],
+    payee_expenses: &[PayeeExpenses],
+    params: &ReportParameters,
+) -> BarChart {
+    let series: Vec<BarChartSerie> = payee_expenses
+        .iter()
+        .take(CHART_PAYEES)
+        .map(|payee| BarChartSerie {
+            key: payee.name.clone(),
+            serie_type: "bar".to_string(),
+            values: months
+                .iter()
+                .zip(&payee.monthly)
+                .map(|(month, value)| {
+                    [
+                        month.and_hms(0, 0, 0).timestamp_millis() as f64,
+                        value.to_f64().unwrap(),
+                    ]
+                })
+                .collect(),
+        })
+        .collect();
+
+    BarChart {
+        id: "payeesChart".to_string(),
+        digit_points: params.main_commodity_decimal_points,
+        series: to_json(series).to_string(),
+    }
+}
+
+fn get_payee_table(
+    months: &[NaiveDate],
+    payee_expenses: &[PayeeExpenses],
+    params: &ReportParameters,
+) -> Table {
+    let mut headers = vec![
+        "Payee".to_string(),
+        "Total".to_string(),
+        "Transactions".to_string(),
+        "Average".to_string(),
+    ];
+    headers.extend(months.iter().map(|month| month.format("%Y/%m").to_string()));
+
+    let rows = payee_expenses
+        .iter()
+        .map(|payee| {
+            let mut columns = vec![
+                TableCell::Text(payee.name.clone()),
+                TableCell::Value(payee.total),
+                TableCell::Text(payee.transactions.to_string()),
+                TableCell::Value(
+                    (payee.total / Decimal::from(payee.transactions))
+                        .round_dp(params.main_commodity_decimal_points),
+                ),
+            ];
+            columns.extend(payee.monthly.iter().map(|value| TableCell::Value(*value)));
+            TableRow { columns }
+        })
+        .collect();
+
+    Table { headers, rows }
+}
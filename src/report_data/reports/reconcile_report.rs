@@ -0,0 +1,323 @@
+use crate::balance_checks::{BalanceCheck, BalanceChecks, PostingPosition};
+use chrono::NaiveDate;
+use ledger_utils::account_balance::AccountBalance;
+use ledger_utils::simplified_ledger;
+use ledger_utils::Amount;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Balance assertions and statement balances compared with the computed balances.
+#[derive(Serialize)]
+pub struct ReconcileReport {
+    pub mismatches: usize,
+    /// The latest first.
+    pub rows: Vec<ReconcileRow>,
+}
+
+#[derive(Serialize)]
+pub struct ReconcileRow {
+    pub date: NaiveDate,
+    pub account: String,
+    pub source: String,
+    pub expected: String,
+    pub actual: String,
+    /// Actual minus expected balance (empty if matching).
+    pub difference: String,
+    pub is_matching: bool,
+    /// Date of the last matching balance of the account.
+    pub last_matching: Option<NaiveDate>,
+    /// The first transaction of the account after the last matching balance
+    /// (not verified by any balance), the difference comes from it or one of the next ones.
+    pub first_unverified: Option<ReconcileTransaction>,
+    /// Transactions since the last matching balance with amount of the difference
+    /// (e.g. duplicates).
+    pub suspects: Vec<ReconcileTransaction>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ReconcileTransaction {
+    pub date: NaiveDate,
+    pub description: String,
+    pub amount: String,
+}
+
+/// Statement balances are checked at the end of the day.
+const END_OF_DAY: PostingPosition = PostingPosition {
+    file: usize::MAX,
+    transaction: usize::MAX,
+    posting: usize::MAX,
+};
+
+/// Balances are computed from the simplified ledgers of the files (in the order of the files),
+/// before they are joined, filtered or converted, so the postings of the balance assertions
+/// can be found.
+pub fn get_reconcile_report(
+    ledgers: &[simplified_ledger::Ledger],
+    balance_checks: &BalanceChecks,
+) -> ReconcileReport {
+    // postings in the order of the joined ledger (sorted by date, then by the file order)
+    let mut postings: Vec<(NaiveDate, PostingPosition, &simplified_ledger::Transaction)> =
+        Vec::new();
+    for (file, ledger) in ledgers.iter().enumerate() {
+        for (transaction_index, transaction) in ledger.transactions.iter().enumerate() {
+            for posting in 0..transaction.postings.len() {
+                postings.push((
+                    transaction.date,
+                    PostingPosition {
+                        file,
+                        transaction: transaction_index,
+                        posting,
+                    },
+                    transaction,
+                ));
+            }
+        }
+    }
+    postings.sort_by_key(|(date, position, _)| (*date, *position));
+
+    let mut checks: Vec<&BalanceCheck> = balance_checks.checks.iter().collect();
+    checks.sort_by_key(|check| get_check_position(check));
+
+    let mut last_matching: HashMap<&str, (NaiveDate, PostingPosition)> = HashMap::new();
+    let mut rows = Vec::new();
+    for check in checks {
+        let check_position = get_check_position(check);
+        let since = last_matching.get(check.account.as_str()).copied();
+        let mut balance = AccountBalance::new();
+        let mut transactions: Vec<(ReconcileTransaction, &Amount)> = Vec::new();
+        for (date, position, transaction) in &postings {
+            // the postings before the assertion (see `BalanceCheck::position`)
+            if (*date, *position) >= check_position {
+                break;
+            }
+            let posting = &transaction.postings[position.posting];
+            if posting.account != check.account {
+                continue;
+            }
+            balance += &posting.amount;
+            if since.is_none_or(|since| (*date, *position) >= since) {
+                transactions.push((
+                    ReconcileTransaction {
+                        date: *date,
+                        description: transaction.description.clone(),
+                        amount: posting.amount.to_string(),
+                    },
+                    &posting.amount,
+                ));
+            }
+        }
+
+        let differences: Vec<Amount> = get_differences(&balance, check.expected.as_ref());
+        let is_matching = differences.is_empty();
+        if is_matching {
+            last_matching.insert(&check.account, check_position);
+        }
+
+        rows.push(ReconcileRow {
+            date: check.date,
+            account: check.account.clone(),
+            source: check.source.clone(),
+            expected: match &check.expected {
+                Some(amount) => amount.to_string(),
+                None => "0".to_string(),
+            },
+            actual: format_balance(&balance),
+            difference: differences
+                .iter()
+                .map(|amount| amount.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+            is_matching,
+            last_matching: since.map(|(date, _)| date),
+            first_unverified: if is_matching {
+                None
+            } else {
+                transactions
+                    .first()
+                    .map(|(transaction, _)| transaction.clone())
+            },
+            suspects: transactions
+                .iter()
+                .filter(|(_, amount)| {
+                    !is_matching
+                        && differences.iter().any(|difference| {
+                            difference.commodity.name == amount.commodity.name
+                                && difference.quantity == amount.quantity
+                        })
+                })
+                .map(|(transaction, _)| transaction.clone())
+                .collect(),
+        });
+    }
+
+    let mismatches = rows.iter().filter(|row| !row.is_matching).count();
+    rows.reverse();
+    ReconcileReport { mismatches, rows }
+}
+
+fn get_check_position(check: &BalanceCheck) -> (NaiveDate, PostingPosition) {
+    (check.date, check.position.unwrap_or(END_OF_DAY))
+}
+
+/// Actual minus expected amounts, the commodities with the same amounts are skipped.
+fn get_differences(balance: &AccountBalance, expected: Option<&Amount>) -> Vec<Amount> {
+    let mut differences: Vec<Amount> = match expected {
+        Some(expected) => {
+            let mut difference = expected.clone();
+            difference.quantity = balance
+                .amounts
+                .get(&expected.commodity.name)
+                .map_or(Decimal::ZERO, |amount| amount.quantity)
+                - expected.quantity;
+            vec![difference]
+        }
+        None => balance.amounts.values().cloned().collect(),
+    };
+    differences.retain(|amount| !amount.quantity.is_zero());
+    differences.sort_by(|a1, a2| a1.commodity.name.cmp(&a2.commodity.name));
+    differences
+}
+
+fn format_balance(balance: &AccountBalance) -> String {
+    let mut amounts: Vec<String> = balance
+        .amounts
+        .values()
+        .filter(|amount| !amount.quantity.is_zero())
+        .map(|amount| amount.to_string())
+        .collect();
+    amounts.sort();
+    if amounts.is_empty() {
+        "0".to_string()
+    } else {
+        amounts.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::StatementBalance;
+    use std::convert::TryFrom;
+
+    fn get_report(ledger: &str, statements: &[StatementBalance]) -> ReconcileReport {
+        let mut ledger = ledger_parser::parse(ledger).unwrap();
+        let mut balance_checks = BalanceChecks::new();
+        balance_checks.take_assertions(&mut ledger);
+        balance_checks.insert_statements(statements, "PLN");
+        let mut ledger = simplified_ledger::Ledger::try_from(ledger).unwrap();
+        balance_checks.locate_assertions(&mut ledger, 0);
+        get_reconcile_report(&[ledger], &balance_checks)
+    }
+
+    fn statement(date: NaiveDate, amount: i64) -> StatementBalance {
+        StatementBalance {
+            account: "Assets:Bank".to_string(),
+            date,
+            amount: Decimal::from(amount),
+            commodity: None,
+        }
+    }
+
+    const LEDGER: &str = r#"
+2024-01-01 Opening
+    Assets:Bank  1000 PLN
+    Equity  -1000 PLN
+
+2024-01-05 Shop
+    Expenses:Food  100 PLN
+    Assets:Bank  -100 PLN = 900 PLN
+
+2024-01-05 Cafe
+    Expenses:Food  10 PLN
+    Assets:Bank  -10 PLN = 890 PLN
+
+2024-01-10 Fuel
+    Expenses:Transport  50 PLN
+    Assets:Bank  -50 PLN
+
+2024-01-10 Fuel
+    Expenses:Transport  50 PLN
+    Assets:Bank  -50 PLN
+"#;
+
+    #[test]
+    fn assertions_are_checked_at_their_postings() {
+        let report = get_report(LEDGER, &[]);
+        assert_eq!(report.mismatches, 0);
+        assert_eq!(report.rows.len(), 2);
+        assert!(report.rows.iter().all(|row| row.is_matching));
+    }
+
+    #[test]
+    fn statements_are_checked_at_the_end_of_the_day() {
+        let report = get_report(LEDGER, &[statement(NaiveDate::from_ymd(2024, 1, 5), 890)]);
+        assert_eq!(report.mismatches, 0);
+        assert_eq!(report.rows[0].source, "statement");
+        assert_eq!(
+            report.rows[0].last_matching,
+            Some(NaiveDate::from_ymd(2024, 1, 5))
+        );
+    }
+
+    #[test]
+    fn mismatch_lists_transactions_since_last_matching_balance() {
+        let report = get_report(LEDGER, &[statement(NaiveDate::from_ymd(2024, 1, 31), 840)]);
+        assert_eq!(report.mismatches, 1);
+        let row = &report.rows[0];
+        assert!(!row.is_matching);
+        assert_eq!(row.actual, "790 PLN");
+        assert_eq!(row.difference, "-50 PLN");
+        assert_eq!(row.last_matching, Some(NaiveDate::from_ymd(2024, 1, 5)));
+        let first_unverified = row.first_unverified.as_ref().unwrap();
+        assert_eq!(first_unverified.date, NaiveDate::from_ymd(2024, 1, 10));
+        assert_eq!(row.suspects.len(), 2);
+    }
+
+    #[test]
+    fn zero_assertion_requires_zero_balance_in_all_commodities() {
+        let report = get_report(
+            r#"
+2024-01-01 Opening
+    Assets:Bank  10 EUR
+    Equity  -10 EUR
+
+2024-01-02 Check
+    Assets:Bank  0 PLN = 0
+    Equity  0 PLN
+"#,
+            &[],
+        );
+        assert_eq!(report.mismatches, 1);
+        assert_eq!(report.rows[0].difference, "10 EUR");
+    }
+
+    #[test]
+    fn assertions_after_postings_split_or_removed_by_simplification() {
+        // the elided amount is split by commodities,
+        // the balance assignment without balance is removed
+        let report = get_report(
+            r#"
+2024-01-01 Opening
+    Assets:Bank  1000 PLN
+    Equity  -1000 PLN
+
+2024-01-02 Trip
+    Expenses:Food  10 PLN
+    Expenses:Travel  5 EUR
+    Assets:Cash
+    Assets:Bank  -100 PLN = 900 PLN
+    Assets:Bank  -50 PLN = 850 PLN
+    Expenses:Fees  150 PLN
+
+2024-01-03 Check
+    Assets:Other  = 0
+    Assets:Bank  -50 PLN = 800 PLN
+    Expenses:Fees  50 PLN
+"#,
+            &[],
+        );
+        assert_eq!(report.rows.len(), 3);
+        assert_eq!(report.mismatches, 0);
+    }
+}
@@ -0,0 +1,226 @@
+use crate::configuration::{Payees, ReportParameters};
+use crate::date_utils::last_day_in_month;
+use crate::report_data::monthly_table::get_amount_value;
+use chrono::{Datelike, NaiveDate};
+use ledger_utils::prices::Prices;
+use ledger_utils::simplified_ledger;
+use rust_decimal::prelude::Zero;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Subscriptions and standing payments detected in the expenses.
+#[derive(Serialize)]
+pub struct RecurringReport {
+    pub main_commodity: String,
+    /// Active payments first, the most expensive first.
+    pub rows: Vec<RecurringRow>,
+}
+
+#[derive(Serialize)]
+pub struct RecurringRow {
+    pub payee: String,
+    pub account: String,
+    pub cadence: String,
+    /// The last amount.
+    pub amount: Decimal,
+    pub annual_cost: Decimal,
+    pub occurrences: usize,
+    pub last_seen: NaiveDate,
+    /// Seen within the last cadence interval (with some tolerance).
+    pub is_active: bool,
+    pub price_changes: Vec<PriceChange>,
+    #[serde(skip)]
+    pub times_per_year: i64,
+}
+
+impl RecurringRow {
+    /// Number of the payments expected in the month, continuing the cadence from the last one.
+    pub fn get_payments_in_month(&self, year: i32, month: u32) -> usize {
+        let months_from_last =
+            (year - self.last_seen.year()) * 12 + month as i32 - self.last_seen.month() as i32;
+        if months_from_last <= 0 {
+            return 0;
+        }
+        if self.times_per_year > 12 {
+            // weekly payments are counted by days
+            let interval = 365 / self.times_per_year;
+            let first_day = NaiveDate::from_ymd(year, month, 1);
+            let last_day = last_day_in_month(year, month);
+            first_day
+                .iter_days()
+                .take_while(|day| *day <= last_day)
+                .filter(|day| (*day - self.last_seen).num_days() % interval == 0)
+                .count()
+        } else {
+            let every_months = 12 / self.times_per_year as i32;
+            usize::from(months_from_last % every_months == 0)
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct PriceChange {
+    pub date: NaiveDate,
+    pub from: Decimal,
+    pub to: Decimal,
+}
+
+/// (name, interval in days, tolerance in days, times per year)
+const CADENCES: &[(&str, i64, i64, i64)] = &[
+    ("Weekly", 7, 2, 52),
+    ("Monthly", 30, 5, 12),
+    ("Quarterly", 91, 10, 4),
+    ("Yearly", 365, 15, 1),
+];
+
+const MIN_OCCURRENCES: usize = 3;
+
+pub fn get_recurring_report(
+    ledger: &simplified_ledger::Ledger,
+    prices: &Prices,
+    payees: &Payees,
+    params: &ReportParameters,
+) -> RecurringReport {
+    // payments by payee and expense account
+    let mut payments: BTreeMap<(String, String), Vec<(NaiveDate, Decimal)>> = BTreeMap::new();
+    let mut payee_names: BTreeMap<String, String> = BTreeMap::new();
+    for transaction in &ledger.transactions {
+        let name = payees.normalize(&transaction.description);
+        let payee = normalize_payee(&name);
+        if payee.is_empty() {
+            continue;
+        }
+        payee_names.entry(payee.clone()).or_insert(name);
+
+        let mut amounts: BTreeMap<&str, Decimal> = BTreeMap::new();
+        for posting in &transaction.postings {
+            if params
+                .expenses
+                .iter()
+                .any(|prefix| posting.account.starts_with(prefix.as_str()))
+            {
+                *amounts
+                    .entry(&posting.account)
+                    .or_insert_with(Decimal::zero) +=
+                    get_amount_value(&posting.amount, transaction.date, prices, params);
+            }
+        }
+        for (account, amount) in amounts {
+            if amount > Decimal::zero() {
+                payments
+                    .entry((payee.clone(), account.to_string()))
+                    .or_default()
+                    .push((transaction.date, amount));
+            }
+        }
+    }
+
+    let last_date = ledger
+        .transactions
+        .iter()
+        .map(|t| t.date)
+        .max()
+        .unwrap_or_else(|| NaiveDate::from_ymd(1970, 1, 1));
+
+    let mut rows: Vec<RecurringRow> = payments
+        .into_iter()
+        .filter_map(|((payee, account), payments)| {
+            get_recurring_row(&payee_names[&payee], account, &payments, last_date)
+        })
+        .collect();
+    rows.sort_by(|r1, r2| {
+        r2.is_active
+            .cmp(&r1.is_active)
+            .then(r2.annual_cost.cmp(&r1.annual_cost))
+            .then(r1.payee.cmp(&r2.payee))
+    });
+
+    RecurringReport {
+        main_commodity: params.main_commodity.clone(),
+        rows,
+    }
+}
+
+/// Payees differing only in numbers or punctuation (e.g. `Netflix 01/2023`) are the same,
+/// after the payee aliases are applied (see `Payees::normalize`).
+fn normalize_payee(description: &str) -> String {
+    description
+        .chars()
+        .map(|c| {
+            if c.is_alphabetic() {
+                c.to_lowercase().next().unwrap_or(c)
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Payments are recurring if (almost) all the intervals between them
+/// match one of the cadences and the amounts are similar.
+fn get_recurring_row(
+    payee: &str,
+    account: String,
+    payments: &[(NaiveDate, Decimal)],
+    last_date: NaiveDate,
+) -> Option<RecurringRow> {
+    if payments.len() < MIN_OCCURRENCES {
+        return None;
+    }
+
+    let mut intervals: Vec<i64> = payments
+        .windows(2)
+        .map(|w| (w[1].0 - w[0].0).num_days())
+        .collect();
+    intervals.sort_unstable();
+    let median_interval = intervals[intervals.len() / 2];
+    let (cadence, days, tolerance, times_per_year) = CADENCES
+        .iter()
+        .find(|(_, days, tolerance, _)| (median_interval - days).abs() <= *tolerance)?;
+    let regular = intervals
+        .iter()
+        .filter(|interval| (*interval - days).abs() <= *tolerance)
+        .count();
+    if regular * 4 < intervals.len() * 3 {
+        return None;
+    }
+
+    let mut amounts: Vec<Decimal> = payments.iter().map(|(_, amount)| *amount).collect();
+    amounts.sort();
+    let median_amount = amounts[amounts.len() / 2];
+    let similar = |amount: Decimal| {
+        amount * Decimal::new(3, 0) >= median_amount * Decimal::new(2, 0)
+            && amount * Decimal::new(2, 0) <= median_amount * Decimal::new(3, 0)
+    };
+    if !amounts.iter().all(|amount| similar(*amount)) {
+        return None;
+    }
+
+    let price_changes = payments
+        .windows(2)
+        .filter(|w| w[0].1 != w[1].1)
+        .map(|w| PriceChange {
+            date: w[1].0,
+            from: w[0].1,
+            to: w[1].1,
+        })
+        .collect();
+
+    let (last_seen, amount) = *payments.last()?;
+    Some(RecurringRow {
+        payee: payee.to_string(),
+        account,
+        cadence: cadence.to_string(),
+        amount,
+        annual_cost: amount * Decimal::from(*times_per_year),
+        occurrences: payments.len(),
+        last_seen,
+        is_active: (last_date - last_seen).num_days() <= days + tolerance,
+        price_changes,
+        times_per_year: *times_per_year,
+    })
+}
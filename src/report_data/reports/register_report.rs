@@ -0,0 +1,115 @@
+use crate::configuration::{self, ReportParameters};
+use crate::report_data::monthly_table::{get_amount_value, get_balance_value};
+use chrono::{Datelike, NaiveDate};
+use ledger_utils::account_balance::AccountBalance;
+use ledger_utils::prices::Prices;
+use ledger_utils::simplified_ledger;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Postings of the accounts (the latest first).
+#[derive(Serialize)]
+pub struct Register {
+    pub main_commodity: String,
+    /// First day of the shown postings (all the postings are shown when not set).
+    pub first_date: Option<NaiveDate>,
+    pub rows: Vec<RegisterRow>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct RegisterRow {
+    pub date: NaiveDate,
+    /// `YYYY/MM` (used by the month filter).
+    pub month: String,
+    pub payee: String,
+    pub account: String,
+    pub amount: String,
+    /// Amount in the main commodity (at the date of the posting).
+    pub value: Decimal,
+    /// Balance of the posting account (or of the account of the register
+    /// including its sub-accounts) after the posting.
+    pub balance: String,
+    /// Balance in the main commodity (at the date of the posting).
+    pub balance_value: Decimal,
+}
+
+/// Postings in the last months of the register (see `configuration::Register`),
+/// of all the accounts or of the account and its sub-accounts.
+/// Balances are computed from all the postings.
+pub fn get_register(
+    ledger: &simplified_ledger::Ledger,
+    prices: &Prices,
+    account: Option<&str>,
+    register: &configuration::Register,
+    params: &ReportParameters,
+) -> Register {
+    let mut transactions: Vec<&simplified_ledger::Transaction> =
+        ledger.transactions.iter().collect();
+    transactions.sort_by_key(|transaction| transaction.date);
+
+    let first_date = transactions
+        .last()
+        .filter(|_| register.months > 0)
+        .map(|transaction| get_first_date(transaction.date, register.months));
+    let sub_account_prefix = account.map(|account| format!("{}:", account));
+
+    let mut balances: HashMap<&str, AccountBalance> = HashMap::new();
+    let mut rows = Vec::new();
+    for transaction in transactions {
+        for posting in &transaction.postings {
+            let balance_account = match (account, &sub_account_prefix) {
+                (Some(account), Some(sub_account_prefix)) => {
+                    if posting.account != account
+                        && !posting.account.starts_with(sub_account_prefix.as_str())
+                    {
+                        continue;
+                    }
+                    account
+                }
+                _ => &posting.account,
+            };
+            let balance = balances.entry(balance_account).or_default();
+            *balance += &posting.amount;
+            if first_date.is_some_and(|first_date| transaction.date < first_date) {
+                continue;
+            }
+
+            let mut amounts: Vec<String> = balance
+                .amounts
+                .values()
+                .filter(|amount| !amount.quantity.is_zero())
+                .map(|amount| amount.to_string())
+                .collect();
+            amounts.sort();
+
+            rows.push(RegisterRow {
+                date: transaction.date,
+                month: transaction.date.format("%Y/%m").to_string(),
+                payee: transaction.description.clone(),
+                account: posting.account.clone(),
+                amount: posting.amount.to_string(),
+                value: get_amount_value(&posting.amount, transaction.date, prices, params),
+                balance: amounts.join(", "),
+                balance_value: get_balance_value(balance, transaction.date, prices, params),
+            });
+        }
+    }
+    rows.reverse();
+
+    Register {
+        main_commodity: params.main_commodity.clone(),
+        first_date,
+        rows,
+    }
+}
+
+/// First day of the month `months - 1` months before the month of the date.
+fn get_first_date(last_date: NaiveDate, months: usize) -> NaiveDate {
+    let month_index = last_date.year() * 12 + last_date.month0() as i32 - (months as i32 - 1);
+    NaiveDate::from_ymd(
+        month_index.div_euclid(12),
+        month_index.rem_euclid(12) as u32 + 1,
+        1,
+    )
+}
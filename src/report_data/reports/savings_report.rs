@@ -0,0 +1,172 @@
+use crate::configuration::ReportParameters;
+use crate::report_data::monthly_table::MonthlyTable;
+use crate::report_data::structures::{
+    BarChart, BarChartSerie, LineChart, LineChartSerie, Table, TableCell, TableRow,
+};
+use crate::report_data::time_series::rolling_sum;
+use chrono::{Datelike, NaiveDate};
+use handlebars::to_json;
+use rust_decimal::prelude::{ToPrimitive, Zero};
+use rust_decimal::Decimal;
+
+pub struct SavingsRow {
+    pub date: NaiveDate,
+    pub income: Decimal,
+    pub expenses: Decimal,
+    pub net_cash_flow: Decimal,
+    /// No savings rate for months without income.
+    pub savings_rate: Option<Decimal>,
+    pub trailing_savings_rate: Option<Decimal>,
+    pub cumulative_savings: Decimal,
+}
+
+/// Savings rate is (income - expenses) / income, in percents.
+pub fn get_savings_rows(
+    monthly_table: &MonthlyTable,
+    params: &ReportParameters,
+) -> Vec<SavingsRow> {
+    // income accounts have negative balances
+    let income = monthly_table.get_monthly_changes(|row| -row.income);
+    let expenses = monthly_table.get_monthly_changes(|row| row.expenses);
+    let trailing_size = params.moving_averages.savings_rate;
+    let trailing_income = rolling_sum(&income, trailing_size);
+    let trailing_expenses = rolling_sum(&expenses, trailing_size);
+
+    let mut rows = Vec::new();
+    let mut cumulative_savings = Decimal::zero();
+    for (pos, row) in monthly_table.rows.iter().enumerate() {
+        let net_cash_flow = income[pos] - expenses[pos];
+        cumulative_savings += net_cash_flow;
+
+        rows.push(SavingsRow {
+            date: row.date,
+            income: income[pos],
+            expenses: expenses[pos],
+            net_cash_flow,
+            savings_rate: get_savings_rate(income[pos], expenses[pos]),
+            trailing_savings_rate: get_savings_rate(trailing_income[pos], trailing_expenses[pos]),
+            cumulative_savings,
+        });
+    }
+
+    rows
+}
+
+fn get_savings_rate(income: Decimal, expenses: Decimal) -> Option<Decimal> {
+    if income.is_zero() {
+        None
+    } else {
+        Some(((income - expenses) * Decimal::new(100, 0) / income).round_dp(2))
+    }
+}
+
+fn get_savings_rate_cell(savings_rate: Option<Decimal>) -> TableCell {
+    match savings_rate {
+        Some(savings_rate) => TableCell::Percent(savings_rate),
+        None => TableCell::Text(String::new()),
+    }
+}
+
+pub fn get_savings_table(rows: &[SavingsRow]) -> Table {
+    let headers = vec![
+        "Date".to_string(),
+        "Income".to_string(),
+        "Expenses".to_string(),
+        "Net Cash Flow".to_string(),
+        "Savings Rate".to_string(),
+        "Savings Rate (Trailing)".to_string(),
+        "Cumulative Savings".to_string(),
+    ];
+
+    let rows = rows
+        .iter()
+        .map(|row| TableRow {
+            columns: vec![
+                TableCell::Month {
+                    year: row.date.year(),
+                    month: row.date.month(),
+                },
+                TableCell::Value(row.income),
+                TableCell::Value(row.expenses),
+                TableCell::Value(row.net_cash_flow),
+                get_savings_rate_cell(row.savings_rate),
+                get_savings_rate_cell(row.trailing_savings_rate),
+                TableCell::Value(row.cumulative_savings),
+            ],
+        })
+        .collect();
+
+    Table { headers, rows }
+}
+
+pub fn get_savings_chart(rows: &[SavingsRow]) -> LineChart {
+    let min_date = rows[0].date.and_hms(0, 0, 0).timestamp_millis();
+    let max_date = rows
+        .last()
+        .unwrap()
+        .date
+        .and_hms(0, 0, 0)
+        .timestamp_millis();
+
+    let mut series_savings_rate = Vec::new();
+    let mut series_trailing_savings_rate = Vec::new();
+    for row in rows {
+        let date = row.date.and_hms(0, 0, 0).timestamp_millis() as f64;
+        // months without income are left out
+        if let Some(savings_rate) = row.savings_rate {
+            series_savings_rate.push([date, savings_rate.to_f64().unwrap()]);
+        }
+        if let Some(trailing_savings_rate) = row.trailing_savings_rate {
+            series_trailing_savings_rate.push([date, trailing_savings_rate.to_f64().unwrap()]);
+        }
+    }
+
+    LineChart {
+        id: "savingsChart".to_string(),
+        min_x: min_date as f64,
+        max_x: max_date as f64,
+        digit_points: 1,
+        series: to_json(vec![
+            LineChartSerie {
+                key: "Savings Rate %".to_string(),
+                area: true,
+                values: series_savings_rate,
+            },
+            LineChartSerie {
+                key: "Savings Rate % (Trailing)".to_string(),
+                area: false,
+                values: series_trailing_savings_rate,
+            },
+        ])
+        .to_string(),
+    }
+}
+
+/// Net cash flow (bars) and cumulative savings (line) in the main commodity.
+pub fn get_savings_cash_flow_chart(rows: &[SavingsRow]) -> BarChart {
+    let mut series_net_cash_flow = Vec::new();
+    let mut series_cumulative_savings = Vec::new();
+    for row in rows {
+        let date = row.date.and_hms(0, 0, 0).timestamp_millis() as f64;
+        series_net_cash_flow.push([date, row.net_cash_flow.to_f64().unwrap()]);
+        series_cumulative_savings.push([date, row.cumulative_savings.to_f64().unwrap()]);
+    }
+
+    BarChart {
+        id: "savingsCashFlowChart".to_string(),
+        digit_points: 0,
+        series: to_json(vec![
+            BarChartSerie {
+                key: "Net Cash Flow".to_string(),
+                serie_type: "bar".to_string(),
+                values: series_net_cash_flow,
+            },
+            BarChartSerie {
+                key: "Cumulative Savings".to_string(),
+                serie_type: "line".to_string(),
+                values: series_cumulative_savings,
+            },
+        ])
+        .to_string(),
+    }
+}
@@ -1,4 +1,5 @@
 use crate::configuration::ReportParameters;
+use crate::report_data::monthly_table::get_balance_value;
 use crate::report_data::structures::TreeNode;
 use chrono::Local;
 use ledger_utils::balance::Balance;
@@ -9,22 +10,34 @@ use rust_decimal::Decimal;
 
 pub fn get_summary_tree(balance: &Balance, prices: &Prices, params: &ReportParameters) -> TreeNode {
     let src_tree_root = TreeBalanceNode::from(balance.clone());
-    convert_tree_node("/", &src_tree_root, prices, params)
+    convert_tree_node("/", "", &src_tree_root, prices, params)
+}
+
+/// Finds the node of a particular account in the tree.
+pub fn find_tree_node<'a>(node: &'a TreeNode, account: &str) -> Option<&'a TreeNode> {
+    if node.account == account {
+        return Some(node);
+    }
+    node.children
+        .iter()
+        .find_map(|child| find_tree_node(child, account))
 }
 
 fn convert_tree_node(
     name: &str,
+    account: &str,
     src_node: &TreeBalanceNode,
     prices: &Prices,
     params: &ReportParameters,
 ) -> TreeNode {
     let mut name = name.to_string();
+    let mut account = account.to_string();
 
-    let amount_main_commodity_value = src_node.balance.value_in_commodity_rounded(
-        &params.main_commodity,
-        params.main_commodity_decimal_points,
+    let amount_main_commodity_value = get_balance_value(
+        &src_node.balance,
         Local::now().date().naive_local(),
-        &prices,
+        prices,
+        params,
     );
 
     let amount_main_commodity =
@@ -48,7 +61,18 @@ fn convert_tree_node(
     let mut children = Vec::new();
 
     for (name, src_node) in &src_node.children {
-        children.push(convert_tree_node(name, src_node, prices, params));
+        let child_account = if account.is_empty() {
+            name.clone()
+        } else {
+            format!("{}:{}", account, name)
+        };
+        children.push(convert_tree_node(
+            name,
+            &child_account,
+            src_node,
+            prices,
+            params,
+        ));
     }
 
     // remove empty (with 0 value) children
@@ -59,9 +83,10 @@ fn convert_tree_node(
 
     // if there is only one child, merge it
     if children.len() == 1 {
-        if let Some(child) = children.iter().next() {
+        if let Some(child) = children.first() {
             if amount_main_commodity_value == child.amount_main_commodity_value {
                 name = format!("{}:{}", name, child.name);
+                account = child.account.clone();
                 children.clear();
             }
         }
@@ -69,6 +94,7 @@ fn convert_tree_node(
 
     TreeNode {
         name,
+        account,
         is_positive: amount_main_commodity_value > Decimal::zero(),
         amount_main_commodity_value,
         amount_main_commodity,
@@ -0,0 +1,134 @@
+use crate::configuration::{ReportParameters, Tags};
+use crate::metadata::Metadata;
+use crate::report_data::monthly_table::get_amount_value;
+use crate::report_data::reports::get_expense_category;
+use chrono::NaiveDate;
+use ledger_utils::prices::Prices;
+use ledger_utils::simplified_ledger;
+use rust_decimal::prelude::Zero;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Expenses per tag and per value of the metadata keys (e.g. per trip).
+#[derive(Serialize)]
+pub struct TagReport {
+    pub main_commodity: String,
+    pub groups: Vec<TagGroup>,
+}
+
+/// Plain tags or values of a metadata key.
+#[derive(Serialize)]
+pub struct TagGroup {
+    pub name: String,
+    /// The biggest expenses first.
+    pub rows: Vec<TagRow>,
+}
+
+#[derive(Serialize)]
+pub struct TagRow {
+    pub value: String,
+    pub first_date: NaiveDate,
+    pub last_date: NaiveDate,
+    pub transactions: usize,
+    pub total: Decimal,
+    pub categories: Vec<TagCategory>,
+}
+
+#[derive(Serialize)]
+pub struct TagCategory {
+    pub category: String,
+    pub value: Decimal,
+}
+
+/// Name of the group of plain tags.
+const TAGS_GROUP: &str = "Tags";
+
+pub fn get_tag_report(
+    ledger: &simplified_ledger::Ledger,
+    prices: &Prices,
+    tags: &Tags,
+    params: &ReportParameters,
+) -> TagReport {
+    // rows by group and value
+    let mut rows: BTreeMap<(String, String), TagRow> = BTreeMap::new();
+    // counted (group, value, transaction index)
+    let mut counted: BTreeSet<(String, String, usize)> = BTreeSet::new();
+
+    for (transaction_pos, transaction) in ledger.transactions.iter().enumerate() {
+        for posting in &transaction.postings {
+            let category = match get_expense_category(&posting.account, params) {
+                Some(category) => category,
+                None => continue,
+            };
+            let metadata = Metadata::of_posting(transaction, posting);
+            let mut keys: Vec<(String, String)> = metadata
+                .tags
+                .iter()
+                .map(|tag| (TAGS_GROUP.to_string(), tag.clone()))
+                .collect();
+            keys.extend(
+                metadata
+                    .values
+                    .iter()
+                    .filter(|(key, _)| tags.keys.is_empty() || tags.keys.contains(key))
+                    .map(|(key, value)| (key.clone(), value.clone())),
+            );
+            if keys.is_empty() {
+                continue;
+            }
+
+            let value = get_amount_value(&posting.amount, transaction.date, prices, params);
+            for (group, tag_value) in keys {
+                let row = rows
+                    .entry((group.clone(), tag_value.clone()))
+                    .or_insert_with(|| TagRow {
+                        value: tag_value.clone(),
+                        first_date: transaction.date,
+                        last_date: transaction.date,
+                        transactions: 0,
+                        total: Decimal::zero(),
+                        categories: Vec::new(),
+                    });
+                row.first_date = row.first_date.min(transaction.date);
+                row.last_date = row.last_date.max(transaction.date);
+                row.total += value;
+                match row.categories.iter_mut().find(|c| c.category == category) {
+                    Some(tag_category) => tag_category.value += value,
+                    None => row.categories.push(TagCategory {
+                        category: category.clone(),
+                        value,
+                    }),
+                }
+                if counted.insert((group, tag_value, transaction_pos)) {
+                    row.transactions += 1;
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<TagGroup> = Vec::new();
+    for ((group, _), mut row) in rows {
+        row.categories
+            .sort_by(|c1, c2| c2.value.cmp(&c1.value).then(c1.category.cmp(&c2.category)));
+        match groups.iter_mut().find(|g| g.name == group) {
+            Some(tag_group) => tag_group.rows.push(row),
+            None => groups.push(TagGroup {
+                name: group,
+                rows: vec![row],
+            }),
+        }
+    }
+    for group in &mut groups {
+        group
+            .rows
+            .sort_by(|r1, r2| r2.total.cmp(&r1.total).then(r1.value.cmp(&r2.value)));
+    }
+    // plain tags first
+    groups.sort_by_key(|group| group.name != TAGS_GROUP);
+
+    TagReport {
+        main_commodity: params.main_commodity.clone(),
+        groups,
+    }
+}
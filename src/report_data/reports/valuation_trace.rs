@@ -0,0 +1,139 @@
+use crate::configuration::ReportParameters;
+use crate::date_utils::last_day_in_month;
+use crate::price_sources::PriceSources;
+use crate::report_data::monthly_table::get_price;
+use chrono::{Local, NaiveDate};
+use ledger_utils::balance::Balance;
+use ledger_utils::monthly_report::MonthlyReport;
+use ledger_utils::prices::Prices;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Prices applied to the commodities at every report date
+/// (month ends and today for the current balances).
+#[derive(Serialize)]
+pub struct ValuationTrace {
+    pub main_commodity: String,
+    pub dates: Vec<ValuationDate>,
+    /// Commodities without a price, their amounts are not counted.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ValuationDate {
+    pub date: NaiveDate,
+    pub prices: Vec<AppliedPrice>,
+    pub missing: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct AppliedPrice {
+    pub commodity: String,
+    pub price: Decimal,
+    pub price_date: NaiveDate,
+    pub source: String,
+}
+
+pub fn get_valuation_trace(
+    monthly_report: &MonthlyReport,
+    prices: &Prices,
+    price_sources: &PriceSources,
+    params: &ReportParameters,
+) -> ValuationTrace {
+    let mut dates = Vec::new();
+    for monthly_balance in &monthly_report.monthly_balances {
+        let mut commodities = get_commodities(&monthly_balance.total, params);
+        commodities.extend(get_commodities(&monthly_balance.monthly_change, params));
+        dates.push(get_valuation_date(
+            last_day_in_month(monthly_balance.year, monthly_balance.month),
+            commodities,
+            prices,
+            price_sources,
+            params,
+        ));
+    }
+    if let Some(monthly_balance) = monthly_report.monthly_balances.last() {
+        dates.push(get_valuation_date(
+            Local::now().date().naive_local(),
+            get_commodities(&monthly_balance.total, params),
+            prices,
+            price_sources,
+            params,
+        ));
+    }
+
+    let mut missing: BTreeMap<&str, Vec<NaiveDate>> = BTreeMap::new();
+    for valuation_date in &dates {
+        for commodity in &valuation_date.missing {
+            missing
+                .entry(commodity)
+                .or_default()
+                .push(valuation_date.date);
+        }
+    }
+    let warnings = missing
+        .into_iter()
+        .map(|(commodity, missing_dates)| {
+            format!(
+                "No price of {} in {} at {} report dates ({} - {}), the amounts are not counted",
+                commodity,
+                params.main_commodity,
+                missing_dates.len(),
+                missing_dates[0].format("%Y/%m/%d"),
+                missing_dates[missing_dates.len() - 1].format("%Y/%m/%d")
+            )
+        })
+        .collect();
+
+    ValuationTrace {
+        main_commodity: params.main_commodity.clone(),
+        dates,
+        warnings,
+    }
+}
+
+/// Commodities of the non-zero account balances (other than the main commodity).
+fn get_commodities<'a>(balance: &'a Balance, params: &ReportParameters) -> BTreeSet<&'a str> {
+    balance
+        .account_balances
+        .values()
+        .flat_map(|account_balance| account_balance.amounts.values())
+        .filter(|amount| {
+            amount.commodity.name != params.main_commodity && !amount.quantity.is_zero()
+        })
+        .map(|amount| amount.commodity.name.as_str())
+        .collect()
+}
+
+/// Prices are found the same way as in the valuation (see `get_price`).
+fn get_valuation_date(
+    date: NaiveDate,
+    commodities: BTreeSet<&str>,
+    prices: &Prices,
+    price_sources: &PriceSources,
+    params: &ReportParameters,
+) -> ValuationDate {
+    let mut applied_prices = Vec::new();
+    let mut missing = Vec::new();
+    for commodity in commodities {
+        match get_price(commodity, date, prices, params) {
+            Some((price_date, price)) => applied_prices.push(AppliedPrice {
+                commodity: commodity.to_string(),
+                price: price.round_dp(6).normalize(),
+                price_date,
+                source: price_sources
+                    .get_source(commodity, &params.main_commodity, price_date)
+                    .unwrap_or("unknown")
+                    .to_string(),
+            }),
+            None => missing.push(commodity.to_string()),
+        }
+    }
+
+    ValuationDate {
+        date,
+        prices: applied_prices,
+        missing,
+    }
+}
@@ -0,0 +1,175 @@
+use crate::configuration::ReportParameters;
+use crate::report_data::monthly_table::MonthlyTable;
+use crate::report_data::reports::expense_breakdown::{get_expense_category, MonthlyExpenses};
+use crate::report_data::time_series::{year_over_year, year_to_date_over_year, YearOverYear};
+use chrono::Datelike;
+use rust_decimal::prelude::Zero;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Months and years-to-date compared with the same periods of the previous year.
+#[derive(Serialize)]
+pub struct YearOverYearReport {
+    pub main_commodity: String,
+    /// Year-to-date of the last month vs the same period of the previous year.
+    pub summary_rows: Vec<YearOverYearRow>,
+    /// Every month (that has the previous year data) and its year-to-date,
+    /// the most recent first.
+    pub rows: Vec<YearOverYearRow>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct YearOverYearRow {
+    pub period: String,
+    pub previous_period: String,
+    pub name: String,
+    /// Expenses increase is a change for the worse.
+    pub is_expense: bool,
+    pub current: Decimal,
+    /// Missing when there is no data for the same period of the previous year.
+    pub previous: Option<Decimal>,
+    pub change: Option<Decimal>,
+    pub change_percent: Option<Decimal>,
+}
+
+/// Monthly values of a single measure (income, expenses, ...).
+struct Measure {
+    name: String,
+    is_expense: bool,
+    values: Vec<Decimal>,
+}
+
+pub fn get_year_over_year_report(
+    monthly_table: &MonthlyTable,
+    monthly_expenses: &[MonthlyExpenses],
+    params: &ReportParameters,
+) -> YearOverYearReport {
+    let months: Vec<(i32, u32)> = monthly_table
+        .rows
+        .iter()
+        .map(|row| (row.date.year(), row.date.month()))
+        .collect();
+    let measures = get_measures(monthly_table, monthly_expenses, params);
+    let monthly_changes: Vec<Vec<YearOverYear>> = measures
+        .iter()
+        .map(|measure| year_over_year(&months, &measure.values))
+        .collect();
+    let year_to_date_changes: Vec<Vec<YearOverYear>> = measures
+        .iter()
+        .map(|measure| year_to_date_over_year(&months, &measure.values))
+        .collect();
+
+    let mut summary_rows = Vec::new();
+    let mut rows = Vec::new();
+    for (pos, &(year, month)) in months.iter().enumerate().rev() {
+        let month_rows: Vec<YearOverYearRow> = measures
+            .iter()
+            .zip(&monthly_changes)
+            .map(|(measure, changes)| {
+                get_row(
+                    format!("{}/{:02}", year, month),
+                    format!("{}/{:02}", year - 1, month),
+                    measure,
+                    &changes[pos],
+                )
+            })
+            .collect();
+        let ytd_rows: Vec<YearOverYearRow> = measures
+            .iter()
+            .zip(&year_to_date_changes)
+            .map(|(measure, changes)| {
+                get_row(
+                    format!("{}/01-{:02}", year, month),
+                    format!("{}/01-{:02}", year - 1, month),
+                    measure,
+                    &changes[pos],
+                )
+            })
+            .collect();
+
+        if pos == months.len() - 1 {
+            summary_rows = ytd_rows.clone();
+        }
+        if month_rows.iter().any(|row| row.previous.is_some()) {
+            rows.extend(month_rows);
+            rows.extend(ytd_rows);
+        }
+    }
+
+    YearOverYearReport {
+        main_commodity: params.main_commodity.clone(),
+        summary_rows,
+        rows,
+    }
+}
+
+/// Income, expenses, savings and expenses of every category.
+fn get_measures(
+    monthly_table: &MonthlyTable,
+    monthly_expenses: &[MonthlyExpenses],
+    params: &ReportParameters,
+) -> Vec<Measure> {
+    // income accounts have negative balances
+    let income = monthly_table.get_monthly_changes(|row| -row.income);
+    let expenses = monthly_table.get_monthly_changes(|row| row.expenses);
+    let savings: Vec<Decimal> = income.iter().zip(&expenses).map(|(i, e)| *i - *e).collect();
+
+    let mut measures = vec![
+        Measure {
+            name: "Income".to_string(),
+            is_expense: false,
+            values: income,
+        },
+        Measure {
+            name: "Expenses".to_string(),
+            is_expense: true,
+            values: expenses,
+        },
+        Measure {
+            name: "Savings".to_string(),
+            is_expense: false,
+            values: savings,
+        },
+    ];
+
+    // monthly expenses have the same months as the monthly table
+    let mut categories: BTreeMap<String, Vec<Decimal>> = BTreeMap::new();
+    for (pos, month) in monthly_expenses.iter().enumerate() {
+        for (account, value) in &month.accounts {
+            if let Some(category) = get_expense_category(account, params) {
+                categories
+                    .entry(category)
+                    .or_insert_with(|| vec![Decimal::zero(); monthly_expenses.len()])[pos] +=
+                    *value;
+            }
+        }
+    }
+    for (category, values) in categories {
+        measures.push(Measure {
+            name: category,
+            is_expense: true,
+            values,
+        });
+    }
+
+    measures
+}
+
+fn get_row(
+    period: String,
+    previous_period: String,
+    measure: &Measure,
+    change: &YearOverYear,
+) -> YearOverYearRow {
+    YearOverYearRow {
+        period,
+        previous_period,
+        name: measure.name.clone(),
+        is_expense: measure.is_expense,
+        current: change.current,
+        previous: change.previous,
+        change: change.change,
+        change_percent: change.change_percent,
+    }
+}
@@ -30,3 +30,36 @@ pub struct LineChartSerie {
     pub area: bool,
     pub values: Vec<[f64; 2]>,
 }
+
+/// Stacked bars with optional lines (e.g. moving averages) drawn over them.
+#[derive(Serialize)]
+pub struct BarChart {
+    pub id: String,
+    pub digit_points: u32,
+    pub series: String,
+}
+
+#[derive(Serialize)]
+pub struct BarChartSerie {
+    pub key: String,
+    /// "bar" or "line"
+    #[serde(rename = "type")]
+    pub serie_type: String,
+    pub values: Vec<[f64; 2]>,
+}
+
+/// Steps from a start value to an end value (e.g. components of a monthly change).
+#[derive(Serialize)]
+pub struct WaterfallChart {
+    pub id: String,
+    pub digit_points: u32,
+    pub steps: String,
+}
+
+#[derive(Serialize)]
+pub struct WaterfallStep {
+    pub label: String,
+    pub value: f64,
+    /// Totals are drawn from zero, other steps continue from the previous total.
+    pub is_total: bool,
+}
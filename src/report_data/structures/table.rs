@@ -1,3 +1,4 @@
+use serde::ser::SerializeMap;
 use serde::Serialize;
 use rust_decimal::Decimal;
 
@@ -15,19 +16,65 @@ pub struct TableRow {
 pub enum TableCell {
     Month { year: i32, month: u32 },
     Value(Decimal),
+    /// Value with a note (e.g. an unusual value).
+    Highlighted { value: Decimal, note: String },
+    Percent(Decimal),
     Text(String),
+    /// Cell linked to the postings of the account (in the month) in the register.
+    Register {
+        cell: Box<TableCell>,
+        account: String,
+        month: Option<String>,
+    },
 }
 
+/// Cells are serialized as objects with formatted `text`
+/// and raw values (`value` or `year` and `month`), so templates can reformat them.
 impl serde::Serialize for TableCell {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         where
             S: serde::ser::Serializer,
     {
-        let text = match self {
-            TableCell::Month { year, month } => format!("{}/{:02}", year, month),
-            TableCell::Value(val) => format!("{}", val),
-            TableCell::Text(val) => format!("{}", val),
+        let mut map = serializer.serialize_map(None)?;
+        self.serialize_entries(&mut map)?;
+        map.end()
+    }
+}
+
+impl TableCell {
+    fn serialize_entries<M>(&self, map: &mut M) -> Result<(), M::Error>
+        where
+            M: SerializeMap,
+    {
+        match self {
+            TableCell::Month { year, month } => {
+                map.serialize_entry("text", &format!("{}/{:02}", year, month))?;
+                map.serialize_entry("year", year)?;
+                map.serialize_entry("month", month)?;
+            }
+            TableCell::Value(val) => {
+                map.serialize_entry("text", &format!("{}", val))?;
+                map.serialize_entry("value", val)?;
+            }
+            TableCell::Highlighted { value, note } => {
+                map.serialize_entry("text", &format!("{}", value))?;
+                map.serialize_entry("value", value)?;
+                map.serialize_entry("highlighted", &true)?;
+                map.serialize_entry("note", note)?;
+            }
+            TableCell::Percent(val) => {
+                map.serialize_entry("text", &format!("{:.1}%", val))?;
+                map.serialize_entry("value", val)?;
+            }
+            TableCell::Text(val) => {
+                map.serialize_entry("text", val)?;
+            }
+            TableCell::Register { cell, account, month } => {
+                cell.serialize_entries(map)?;
+                map.serialize_entry("register_account", account)?;
+                map.serialize_entry("register_month", month)?;
+            }
         };
-        serializer.serialize_str(&text)
+        Ok(())
     }
 }
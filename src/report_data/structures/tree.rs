@@ -4,6 +4,8 @@ use rust_decimal::Decimal;
 #[derive(Serialize)]
 pub struct TreeNode {
     pub name: String,
+    /// Full account name (empty for the root node).
+    pub account: String,
     pub is_positive: bool,
     pub amount_main_commodity_value: Decimal,
     pub amount_main_commodity: String,
@@ -0,0 +1,212 @@
+use crate::configuration::{MovingAverage, MovingAverageKind};
+use rust_decimal::prelude::Zero;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// Average of the last `window` values.
+/// Until there are enough values (warm-up) the average of the available values is used.
+pub fn simple_moving_average(values: &[Decimal], window: usize) -> Vec<Decimal> {
+    let window = window.max(1);
+    rolling_sum(values, window)
+        .into_iter()
+        .enumerate()
+        .map(|(pos, sum)| sum / Decimal::from(window.min(pos + 1)))
+        .collect()
+}
+
+/// Exponential moving average with smoothing factor `2 / (window + 1)`,
+/// starting with the first value.
+pub fn exponential_moving_average(values: &[Decimal], window: usize) -> Vec<Decimal> {
+    let alpha = Decimal::new(2, 0) / Decimal::from(window.max(1) + 1);
+    let mut result: Vec<Decimal> = Vec::with_capacity(values.len());
+    for value in values {
+        let ema = match result.last() {
+            Some(previous) => alpha * *value + (Decimal::new(1, 0) - alpha) * *previous,
+            None => *value,
+        };
+        result.push(ema);
+    }
+    result
+}
+
+/// Sum of the last `window` values (or of the available values during warm-up).
+pub fn rolling_sum(values: &[Decimal], window: usize) -> Vec<Decimal> {
+    let window = window.max(1);
+    let mut sum = Decimal::zero();
+    values
+        .iter()
+        .enumerate()
+        .map(|(pos, value)| {
+            sum += *value;
+            if pos >= window {
+                sum -= values[pos - window];
+            }
+            sum
+        })
+        .collect()
+}
+
+pub fn moving_average(values: &[Decimal], moving_average: &MovingAverage) -> Vec<Decimal> {
+    match moving_average.kind {
+        MovingAverageKind::Sma => simple_moving_average(values, moving_average.window),
+        MovingAverageKind::Ema => exponential_moving_average(values, moving_average.window),
+    }
+}
+
+/// Value of a period compared with the same period of the previous year.
+#[derive(Clone, Copy)]
+pub struct YearOverYear {
+    pub current: Decimal,
+    /// Missing during warm-up (the data starts later than the previous period).
+    pub previous: Option<Decimal>,
+    pub change: Option<Decimal>,
+    /// Relative to the absolute previous value, missing when the previous value is zero.
+    pub change_percent: Option<Decimal>,
+}
+
+impl YearOverYear {
+    fn new(current: Decimal, previous: Option<Decimal>) -> YearOverYear {
+        YearOverYear {
+            current,
+            previous,
+            change: previous.map(|previous| current - previous),
+            change_percent: previous.and_then(|previous| {
+                if previous.is_zero() {
+                    None
+                } else {
+                    Some(((current - previous) * Decimal::new(100, 0) / previous.abs()).round_dp(1))
+                }
+            }),
+        }
+    }
+}
+
+/// Monthly values compared with the same months of the previous year.
+/// `months` are (year, month) of the values, months without a value count as zero.
+pub fn year_over_year(months: &[(i32, u32)], values: &[Decimal]) -> Vec<YearOverYear> {
+    let by_month: HashMap<(i32, u32), Decimal> =
+        months.iter().copied().zip(values.iter().copied()).collect();
+    let first_month = months.first().copied();
+
+    months
+        .iter()
+        .zip(values)
+        .map(|(&(year, month), &current)| {
+            let previous = if first_month.is_some_and(|first| first <= (year - 1, month)) {
+                Some(get_value(&by_month, year - 1, month))
+            } else {
+                None
+            };
+            YearOverYear::new(current, previous)
+        })
+        .collect()
+}
+
+/// Year-to-date sums (from January to the month) of the monthly values
+/// compared with the same periods of the previous year.
+/// The previous year-to-date is missing when the data starts after its January.
+pub fn year_to_date_over_year(months: &[(i32, u32)], values: &[Decimal]) -> Vec<YearOverYear> {
+    let by_month: HashMap<(i32, u32), Decimal> =
+        months.iter().copied().zip(values.iter().copied()).collect();
+    let first_month = months.first().copied();
+    let year_to_date = |year: i32, month: u32| -> Decimal {
+        (1..=month)
+            .map(|month| get_value(&by_month, year, month))
+            .sum()
+    };
+
+    months
+        .iter()
+        .map(|&(year, month)| {
+            let previous = if first_month.is_some_and(|first| first <= (year - 1, 1)) {
+                Some(year_to_date(year - 1, month))
+            } else {
+                None
+            };
+            YearOverYear::new(year_to_date(year, month), previous)
+        })
+        .collect()
+}
+
+fn get_value(by_month: &HashMap<(i32, u32), Decimal>, year: i32, month: u32) -> Decimal {
+    by_month
+        .get(&(year, month))
+        .copied()
+        .unwrap_or_else(Decimal::zero)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimals(values: &[i64]) -> Vec<Decimal> {
+        values.iter().map(|value| Decimal::from(*value)).collect()
+    }
+
+    #[test]
+    fn rolling_sum_with_warm_up() {
+        assert_eq!(
+            rolling_sum(&decimals(&[1, 2, 3, 4]), 2),
+            decimals(&[1, 3, 5, 7])
+        );
+        assert_eq!(rolling_sum(&decimals(&[1, 2]), 0), decimals(&[1, 2]));
+    }
+
+    #[test]
+    fn simple_moving_average_with_warm_up() {
+        assert_eq!(
+            simple_moving_average(&decimals(&[2, 4, 6, 8]), 3),
+            decimals(&[2, 3, 4, 6])
+        );
+    }
+
+    #[test]
+    fn exponential_moving_average_starts_with_first_value() {
+        // smoothing factor 2 / (3 + 1) = 0.5
+        assert_eq!(
+            exponential_moving_average(&decimals(&[4, 8, 0]), 3),
+            decimals(&[4, 6, 3])
+        );
+        assert!(exponential_moving_average(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn year_over_year_with_warm_up_and_missing_months() {
+        // 2023/02 has no value
+        let months = [(2023, 1), (2023, 3), (2024, 1), (2024, 2), (2024, 3)];
+        let values = decimals(&[100, -50, 150, 20, -100]);
+        let result = year_over_year(&months, &values);
+
+        assert_eq!(result[0].previous, None);
+        assert_eq!(result[1].change, None);
+
+        assert_eq!(result[2].previous, Some(Decimal::from(100)));
+        assert_eq!(result[2].change, Some(Decimal::from(50)));
+        assert_eq!(result[2].change_percent, Some(Decimal::from(50)));
+
+        assert_eq!(result[3].previous, Some(Decimal::zero()));
+        assert_eq!(result[3].change, Some(Decimal::from(20)));
+        assert_eq!(result[3].change_percent, None);
+
+        // relative to the absolute previous value
+        assert_eq!(result[4].change, Some(Decimal::from(-50)));
+        assert_eq!(result[4].change_percent, Some(Decimal::from(-100)));
+    }
+
+    #[test]
+    fn year_to_date_over_year_requires_whole_previous_period() {
+        let months = [(2022, 2), (2023, 1), (2023, 2), (2024, 1), (2024, 2)];
+        let values = decimals(&[10, 100, 50, 120, 60]);
+        let result = year_to_date_over_year(&months, &values);
+
+        // the data starts after January 2022
+        assert_eq!(result[1].current, Decimal::from(100));
+        assert_eq!(result[1].previous, None);
+        assert_eq!(result[2].current, Decimal::from(150));
+        assert_eq!(result[2].previous, None);
+
+        assert_eq!(result[4].current, Decimal::from(180));
+        assert_eq!(result[4].previous, Some(Decimal::from(150)));
+        assert_eq!(result[4].change_percent, Some(Decimal::from(20)));
+    }
+}
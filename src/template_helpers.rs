@@ -0,0 +1,122 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use handlebars::{handlebars_helper, Handlebars, JsonValue, RenderError};
+use rust_decimal::prelude::{FromPrimitive, Zero};
+use rust_decimal::Decimal;
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// Registers helpers that allow templates to format and compute values.
+///
+/// - `{{money value "PLN" decimals=2}}` - value with commodity, e.g. `1234.50 PLN`
+/// - `{{percent a b decimals=1}}` - `a` as a percentage of `b`, e.g. `12.5%`
+/// - `{{date value "%Y/%m"}}` - formats a date (`YYYY-MM-DD` string or timestamp in milliseconds)
+/// - `{{abs value}}` - absolute value
+/// - `{{sign-class value}}` - `positive`, `negative` or `zero` (to be used as CSS class)
+/// - `{{account-file account}}` - path of the account page in the static site
+pub fn register_helpers(reg: &mut Handlebars) {
+    reg.register_helper("money", Box::new(money));
+    reg.register_helper("percent", Box::new(percent));
+    reg.register_helper("date", Box::new(date));
+    reg.register_helper("abs", Box::new(abs));
+    reg.register_helper("sign-class", Box::new(sign_class));
+    reg.register_helper("account-file", Box::new(account_file));
+}
+
+/// Path of the account page (relative to the static site root).
+/// The encoding is reversible, so different accounts never share a page:
+/// `:` becomes `.` and other special characters are `_` with the hex code of their bytes,
+/// e.g. `Assets:Bank A` is `Assets.Bank_20A`.
+pub fn account_file_name(account: &str) -> String {
+    let mut name = String::new();
+    for c in account.chars() {
+        if c.is_alphanumeric() || c == '-' {
+            name.push(c);
+        } else if c == ':' {
+            name.push('.');
+        } else {
+            for byte in c.to_string().bytes() {
+                name.push_str(&format!("_{:02X}", byte));
+            }
+        }
+    }
+    format!("accounts/{}.html", name)
+}
+
+handlebars_helper!(money: |value: Json, commodity: str, {decimals: u64 = 2}| {
+    let value = to_decimal("money", value)?;
+    format!("{:.*} {}", decimals as usize, value, commodity)
+});
+
+handlebars_helper!(percent: |a: Json, b: Json, {decimals: u64 = 1}| {
+    let a = to_decimal("percent", a)?;
+    let b = to_decimal("percent", b)?;
+    if b.is_zero() {
+        "".to_string()
+    } else {
+        format!("{:.*}%", decimals as usize, a * Decimal::new(100, 0) / b)
+    }
+});
+
+handlebars_helper!(date: |value: Json, fmt: str| {
+    let naive_date = to_date(value)
+        .ok_or_else(|| RenderError::new(format!("`date` helper: {} is not a date", value)))?;
+    // the format comes from the (possibly custom) templates
+    let mut text = String::new();
+    write!(text, "{}", naive_date.format(fmt))
+        .map_err(|_| RenderError::new(format!("`date` helper: wrong format '{}'", fmt)))?;
+    text
+});
+
+handlebars_helper!(abs: |value: Json| {
+    to_decimal("abs", value)?.abs().to_string()
+});
+
+handlebars_helper!(sign_class: |value: Json| {
+    let value = to_decimal("sign-class", value)?;
+    if value > Decimal::zero() {
+        "positive"
+    } else if value < Decimal::zero() {
+        "negative"
+    } else {
+        "zero"
+    }
+});
+
+handlebars_helper!(account_file: |account: str| {
+    account_file_name(account)
+});
+
+/// Decimals are serialized as strings, so both strings and numbers are accepted.
+/// Missing values (e.g. misspelled fields) are errors.
+fn to_decimal(helper_name: &str, value: &JsonValue) -> Result<Decimal, RenderError> {
+    let decimal = match value {
+        JsonValue::String(text) => Decimal::from_str(text).ok(),
+        JsonValue::Number(number) => {
+            if let Some(number) = number.as_i64() {
+                Some(Decimal::from(number))
+            } else {
+                number.as_f64().and_then(Decimal::from_f64)
+            }
+        }
+        _ => None,
+    };
+
+    decimal.ok_or_else(|| {
+        RenderError::new(format!(
+            "`{}` helper: {} is not a number",
+            helper_name, value
+        ))
+    })
+}
+
+/// Timestamps out of the supported range are not dates.
+fn to_date(value: &JsonValue) -> Option<NaiveDate> {
+    match value {
+        JsonValue::String(text) => NaiveDate::parse_from_str(text, "%Y-%m-%d").ok(),
+        JsonValue::Number(number) => number
+            .as_f64()
+            .and_then(|millis| NaiveDateTime::from_timestamp_opt((millis / 1000.0) as i64, 0))
+            .map(|datetime| datetime.date()),
+        _ => None,
+    }
+}
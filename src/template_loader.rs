@@ -0,0 +1,73 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+/// Loads report templates.
+///
+/// Every template can be overridden by a file with the same name
+/// placed in the user templates directory.
+/// Templates that are not found there fall back to the built-in ones.
+pub struct TemplateLoader {
+    templates_dir: Option<PathBuf>,
+}
+
+impl TemplateLoader {
+    pub fn new(templates_dir: Option<&str>) -> TemplateLoader {
+        TemplateLoader {
+            templates_dir: templates_dir.map(PathBuf::from),
+        }
+    }
+
+    pub fn load(&self, file_name: &str) -> Result<String, Box<dyn Error>> {
+        if let Some(templates_dir) = &self.templates_dir {
+            let path = templates_dir.join(file_name);
+            if path.is_file() {
+                return Ok(std::fs::read_to_string(path)?);
+            }
+        }
+
+        get_built_in_template(file_name)
+            .map(|content| content.to_string())
+            .ok_or_else(|| format!("Unknown template: {}", file_name).into())
+    }
+}
+
+fn get_built_in_template(file_name: &str) -> Option<&'static str> {
+    match file_name {
+        "main.hbs" => Some(include_str!("templates/main.hbs")),
+        "page.hbs" => Some(include_str!("templates/page.hbs")),
+        "index.hbs" => Some(include_str!("templates/index.hbs")),
+        "summary.hbs" => Some(include_str!("templates/summary.hbs")),
+        "assets.hbs" => Some(include_str!("templates/assets.hbs")),
+        "net_worth.hbs" => Some(include_str!("templates/net_worth.hbs")),
+        "expenses.hbs" => Some(include_str!("templates/expenses.hbs")),
+        "expense_categories.hbs" => Some(include_str!("templates/expense_categories.hbs")),
+        "anomalies.hbs" => Some(include_str!("templates/anomalies.hbs")),
+        "budget.hbs" => Some(include_str!("templates/budget.hbs")),
+        "forecast.hbs" => Some(include_str!("templates/forecast.hbs")),
+        "income.hbs" => Some(include_str!("templates/income.hbs")),
+        "payees.hbs" => Some(include_str!("templates/payees.hbs")),
+        "recurring.hbs" => Some(include_str!("templates/recurring.hbs")),
+        "tags.hbs" => Some(include_str!("templates/tags.hbs")),
+        "savings.hbs" => Some(include_str!("templates/savings.hbs")),
+        "allocation.hbs" => Some(include_str!("templates/allocation.hbs")),
+        "attribution.hbs" => Some(include_str!("templates/attribution.hbs")),
+        "exposure.hbs" => Some(include_str!("templates/exposure.hbs")),
+        "investments.hbs" => Some(include_str!("templates/investments.hbs")),
+        "year_over_year.hbs" => Some(include_str!("templates/year_over_year.hbs")),
+        "register.hbs" => Some(include_str!("templates/register.hbs")),
+        "reconcile.hbs" => Some(include_str!("templates/reconcile.hbs")),
+        "data_quality.hbs" => Some(include_str!("templates/data_quality.hbs")),
+        "account.hbs" => Some(include_str!("templates/account.hbs")),
+        "table.hbs" => Some(include_str!("templates/table.hbs")),
+        "register_table.hbs" => Some(include_str!("templates/register_table.hbs")),
+        "valuation_footer.hbs" => Some(include_str!("templates/valuation_footer.hbs")),
+        "tree.hbs" => Some(include_str!("templates/tree.hbs")),
+        "tree_node.hbs" => Some(include_str!("templates/tree_node.hbs")),
+        "area_chart.hbs" => Some(include_str!("templates/area_chart.hbs")),
+        "line_chart.hbs" => Some(include_str!("templates/line_chart.hbs")),
+        "bar_chart.hbs" => Some(include_str!("templates/bar_chart.hbs")),
+        "waterfall_chart.hbs" => Some(include_str!("templates/waterfall_chart.hbs")),
+        "main.css" => Some(include_str!("templates/main.css")),
+        _ => None,
+    }
+}
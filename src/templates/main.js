@@ -27,6 +27,91 @@ function openPage(pageName, button) {
     window.dispatchEvent(tabChangedEvent);
 }
 
+function showExpenseBreakdown(id) {
+    var i, breakdowns;
+    breakdowns = document.getElementsByClassName("expense_breakdown");
+    for (i = 0; i < breakdowns.length; i++) {
+        breakdowns[i].classList.remove("expense_breakdown_active");
+    }
+    document.getElementById(id).classList.add("expense_breakdown_active");
+
+    // let the charts resize to the visible container
+    window.dispatchEvent(tabChangedEvent);
+}
+
+// Shows the postings of the account (and its sub-accounts) in the month (optional).
+// Pages of the static site without the register open the register page.
+function showRegister(account, month) {
+    var transactions = document.getElementById("transactions");
+    if (!transactions) {
+        window.location.href = document.body.dataset.rootPath + "register.html#account=" +
+            encodeURIComponent(account) + "&month=" + encodeURIComponent(month);
+        return;
+    }
+
+    var register = transactions.querySelector(".register");
+    register.dataset.account = account;
+    register.dataset.month = month;
+    filterRegister(register);
+
+    var button = document.querySelector(".tablink[data-page='register']");
+    if (button) {
+        openPage("register", button);
+    }
+}
+
+function clearRegisterFilter(register) {
+    register.dataset.account = "";
+    register.dataset.month = "";
+    register.querySelector(".register_search").value = "";
+    filterRegister(register);
+}
+
+function filterRegister(register) {
+    var account = register.dataset.account || "";
+    var month = register.dataset.month || "";
+    var search = register.querySelector(".register_search").value.toLowerCase();
+
+    var rows = register.querySelectorAll("tbody tr");
+    for (var i = 0; i < rows.length; i++) {
+        var row = rows[i];
+        var visible = (!account || row.dataset.account === account ||
+                row.dataset.account.startsWith(account + ":")) &&
+            (!month || row.dataset.month === month) &&
+            (!search || row.textContent.toLowerCase().indexOf(search) >= 0);
+        row.style.display = visible ? "" : "none";
+    }
+
+    var note = [account, month].filter(function(text) { return text; }).join(", ");
+    register.querySelector(".register_filter_note").textContent = note;
+    register.querySelector(".register_filter_clear").style.display = note ? "" : "none";
+}
+
+function handleRegister() {
+    var links = document.getElementsByClassName("register_link");
+    for (var i = 0; i < links.length; i++) {
+        links[i].addEventListener("click", function(event) {
+            event.preventDefault();
+            // don't toggle the tree node
+            event.stopPropagation();
+            showRegister(this.dataset.account, this.dataset.month || "");
+        });
+    }
+
+    var registers = document.getElementsByClassName("register");
+    for (i = 0; i < registers.length; i++) {
+        filterRegister(registers[i]);
+    }
+
+    // filter from the link of another page (static site)
+    if (document.getElementById("transactions") && window.location.hash) {
+        var params = new URLSearchParams(window.location.hash.substring(1));
+        if (params.get("account")) {
+            showRegister(params.get("account"), params.get("month") || "");
+        }
+    }
+}
+
 function handleTree() {
     var toggler = document.getElementsByClassName("tree_caret");
     var i;
@@ -41,7 +126,12 @@ function handleTree() {
 
 window.onload = function () {
     // Get the element with id="defaultOpen" and click on it
-    document.getElementById("defaultOpen").click();
+    // (there are no tabs in the static site)
+    var defaultOpen = document.getElementById("defaultOpen");
+    if (defaultOpen) {
+        defaultOpen.click();
+    }
 
     handleTree();
+    handleRegister();
 }
\ No newline at end of file